@@ -16,81 +16,436 @@
 //! The pipeline is deterministic: given the same Scheme and hardware profile,
 //! it always produces the same output.
 
-use crate::core::SegmentId;
-use crate::scheme::abstract_scheme::{LogicalAddress, Scheme};
+use crate::core::{Segment, SegmentId};
+use crate::scheme::abstract_scheme::{LogicalAddress, Scheme, SchemeId};
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+type PlacementFn = Arc<dyn Fn(&Segment, &LogicalAddress) -> HardwareResource + Send + Sync>;
 
 /// Target hardware profile.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub enum HardwareProfile {
-    /// Generic CPU with N cores.
-    Cpu { cores: usize },
-    /// FPGA with a certain number of configurable logic blocks.
-    Fpga { clbs: usize },
+    /// Generic CPU with N cores spread evenly over `numa_nodes` NUMA nodes.
+    /// Cores are numbered node‑major; when the split is uneven the first
+    /// `cores % numa_nodes` nodes get one extra core. Compiling requires
+    /// `1 <= numa_nodes <= cores`.
+    Cpu { cores: usize, numa_nodes: usize },
+    /// FPGA with a certain number of configurable logic blocks,
+    /// each holding at most `capacity` Segments.
+    Fpga { clbs: usize, capacity: usize },
     /// Processing‑in‑memory unit with dedicated observation logic.
     Pim { units: usize },
-    /// Custom hardware description.
-    Custom(String),
+    /// GPU with `sms` streaming multiprocessors executing warps of `warp_size` lanes.
+    Gpu { sms: usize, warp_size: usize },
+    /// Custom hardware: `mapper` places each mapped Segment.
+    Custom { name: String, mapper: PlacementFn },
+}
+
+impl std::fmt::Debug for HardwareProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HardwareProfile::Cpu { cores, numa_nodes } => f
+                .debug_struct("Cpu")
+                .field("cores", cores)
+                .field("numa_nodes", numa_nodes)
+                .finish(),
+            HardwareProfile::Fpga { clbs, capacity } => f
+                .debug_struct("Fpga")
+                .field("clbs", clbs)
+                .field("capacity", capacity)
+                .finish(),
+            HardwareProfile::Pim { units } => f.debug_struct("Pim").field("units", units).finish(),
+            HardwareProfile::Gpu { sms, warp_size } => f
+                .debug_struct("Gpu")
+                .field("sms", sms)
+                .field("warp_size", warp_size)
+                .finish(),
+            HardwareProfile::Custom { name, .. } => f
+                .debug_struct("Custom")
+                .field("name", name)
+                .field("mapper", &"<fn>")
+                .finish(),
+        }
+    }
+}
+
+impl HardwareProfile {
+    /// NUMA node hosting `core`, or `None` for non‑CPU profiles or out‑of‑range cores.
+    pub fn numa_node_of(&self, core: usize) -> Option<usize> {
+        match self {
+            HardwareProfile::Cpu { cores, numa_nodes } if core < *cores && *numa_nodes > 0 => {
+                let base = cores / numa_nodes;
+                let extra = cores % numa_nodes;
+                // The first `extra` nodes hold `base + 1` cores each.
+                let wide = extra * (base + 1);
+                if core < wide {
+                    Some(core / (base + 1))
+                } else {
+                    Some(extra + (core - wide) / base)
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Error type for compilation failures.
+#[derive(Debug, thiserror::Error)]
+pub enum CompileError {
+    #[error(
+        "FPGA capacity exceeded: {unplaced} of {segments} segments could not be placed \
+         ({clbs} CLBs x {capacity})"
+    )]
+    FpgaCapacityExceeded {
+        segments: usize,
+        clbs: usize,
+        capacity: usize,
+        unplaced: usize,
+    },
+    #[error("Cyclic dependency among {} segments", .0.len())]
+    CyclicDependency(Vec<SegmentId>),
+    #[error("Invalid CPU profile: {cores} cores on {numa_nodes} NUMA nodes")]
+    InvalidCpuProfile { cores: usize, numa_nodes: usize },
+    #[error("Invalid GPU profile: {sms} SMs with warp size {warp_size}")]
+    InvalidGpuProfile { sms: usize, warp_size: usize },
 }
 
 /// Result of the compilation pipeline.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CompiledScheme {
     /// Original Scheme (for reference).
     pub scheme: Scheme,
     /// Mapping from SegmentId to resolved logical address.
     pub logical_addresses: HashMap<SegmentId, LogicalAddress>,
+    /// Segments the memory layout could not map (sorted by SegmentId).
+    /// These receive no hardware placement.
+    pub unmapped_segments: Vec<SegmentId>,
+    /// Dependency layers: Segments within a layer have no dependencies on each
+    /// other and can be observed concurrently once all earlier layers are done.
+    pub parallel_layers: Vec<Vec<SegmentId>>,
     /// Mapping from SegmentId to hardware resource.
     pub hardware_placement: HashMap<SegmentId, HardwareResource>,
     /// Generated observation code (placeholder).
     pub observation_code: Vec<u8>,
 }
 
+impl CompiledScheme {
+    /// True when every Segment of the Scheme was mapped to a logical address
+    /// and placed on a hardware resource.
+    pub fn is_complete(&self) -> bool {
+        self.unmapped_segments.is_empty()
+            && self
+                .scheme
+                .segment_ids()
+                .all(|id| self.hardware_placement.contains_key(id))
+    }
+}
+
+/// Wall‑clock time spent in each pipeline stage, from `compile_with_report`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompilationReport {
+    pub structural_analysis: Duration,
+    pub memory_resolution: Duration,
+    pub hardware_mapping: Duration,
+    pub code_generation: Duration,
+}
+
+impl CompilationReport {
+    /// Stage names and durations in pipeline order.
+    pub fn stages(&self) -> [(&'static str, Duration); 4] {
+        [
+            ("structural_analysis", self.structural_analysis),
+            ("memory_resolution", self.memory_resolution),
+            ("hardware_mapping", self.hardware_mapping),
+            ("code_generation", self.code_generation),
+        ]
+    }
+
+    pub fn total(&self) -> Duration {
+        self.stages().iter().map(|(_, d)| *d).sum()
+    }
+}
+
+/// Memo of compiled Schemes keyed by Scheme, profile and pipeline options.
+///
+/// Compilation is deterministic, so a Scheme compiled twice against the same
+/// profile yields the same result. The profile part of the key covers its
+/// parameters (`Cpu { cores: 4, .. }` and `Cpu { cores: 8, .. }` are distinct
+/// entries), not just the variant. `Custom` profiles are keyed by their name
+/// and the identity of the mapper `Arc`, so only clones of the same mapper
+/// share an entry. Failed compilations are not cached.
+#[derive(Debug, Default)]
+pub struct CompilationCache {
+    entries: HashMap<CacheKey, CompiledScheme>,
+}
+
+impl CompilationCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    scheme: SchemeId,
+    profile: ProfileKey,
+    compact: bool,
+    partition_spaces: bool,
+}
+
+impl CacheKey {
+    fn of(pipeline: &CompilerPipeline) -> Self {
+        let profile = match &pipeline.profile {
+            HardwareProfile::Cpu { cores, numa_nodes } => ProfileKey::Cpu {
+                cores: *cores,
+                numa_nodes: *numa_nodes,
+            },
+            HardwareProfile::Fpga { clbs, capacity } => ProfileKey::Fpga {
+                clbs: *clbs,
+                capacity: *capacity,
+            },
+            HardwareProfile::Pim { units } => ProfileKey::Pim { units: *units },
+            HardwareProfile::Gpu { sms, warp_size } => ProfileKey::Gpu {
+                sms: *sms,
+                warp_size: *warp_size,
+            },
+            HardwareProfile::Custom { name, mapper } => ProfileKey::Custom {
+                name: name.clone(),
+                mapper: MapperKey(mapper.clone()),
+            },
+        };
+        Self {
+            scheme: *pipeline.scheme.id(),
+            profile,
+            compact: pipeline.compact,
+            partition_spaces: pipeline.partition_spaces,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ProfileKey {
+    Cpu { cores: usize, numa_nodes: usize },
+    Fpga { clbs: usize, capacity: usize },
+    Pim { units: usize },
+    Gpu { sms: usize, warp_size: usize },
+    Custom { name: String, mapper: MapperKey },
+}
+
+/// Compares mappers by `Arc` identity. Holding the `Arc` keeps the closure
+/// alive, so its address cannot be reused by another mapper while cached.
+#[derive(Clone)]
+struct MapperKey(PlacementFn);
+
+impl MapperKey {
+    fn addr(&self) -> *const () {
+        Arc::as_ptr(&self.0) as *const ()
+    }
+}
+
+impl PartialEq for MapperKey {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.addr(), other.addr())
+    }
+}
+
+impl Eq for MapperKey {}
+
+impl std::hash::Hash for MapperKey {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.addr().hash(state);
+    }
+}
+
+impl std::fmt::Debug for MapperKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MapperKey({:p})", self.addr())
+    }
+}
+
 /// A hardware resource (core, CLB, PIM unit, etc.).
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum HardwareResource {
     CpuCore(usize),
     FpgaClb(usize),
     PimUnit(usize),
+    GpuLane {
+        sm: usize,
+        lane: usize,
+    },
+    /// Resource chosen by a `HardwareProfile::Custom` mapper.
+    Custom(u64),
+}
+
+impl HardwareResource {
+    /// Index of the resource owning local memory: the core, CLB or PIM unit
+    /// number, the SM for GPU lanes, or the custom id.
+    pub fn index(&self) -> u64 {
+        match self {
+            HardwareResource::CpuCore(i)
+            | HardwareResource::FpgaClb(i)
+            | HardwareResource::PimUnit(i) => *i as u64,
+            HardwareResource::GpuLane { sm, .. } => *sm as u64,
+            HardwareResource::Custom(i) => *i,
+        }
+    }
 }
 
 /// The compiler pipeline.
 pub struct CompilerPipeline {
     scheme: Scheme,
     profile: HardwareProfile,
+    /// Renumber logical offsets densely after layout resolution
+    compact: bool,
+    /// Give each hardware resource its own address space after placement
+    partition_spaces: bool,
 }
 
 impl CompilerPipeline {
     /// Creates a new pipeline for the given Scheme and hardware profile.
     pub fn new(scheme: Scheme, profile: HardwareProfile) -> Self {
-        Self { scheme, profile }
+        Self {
+            scheme,
+            profile,
+            compact: false,
+            partition_spaces: false,
+        }
+    }
+
+    /// Enables `compact_addresses` after memory‑layout resolution, closing the
+    /// gaps a sparse Scheme leaves in the logical address range.
+    pub fn with_compaction(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Enables `assign_address_spaces` after hardware mapping, modelling
+    /// distributed memory where each resource owns its own address space.
+    pub fn with_address_spaces(mut self, partition: bool) -> Self {
+        self.partition_spaces = partition;
+        self
     }
 
     /// Runs the complete pipeline, returning a `CompiledScheme`.
-    pub fn compile(self) -> CompiledScheme {
-        let addresses = self.stage_memory_layout_resolution();
-        let hardware_placement = self.stage_hardware_mapping(&addresses);
-        let observation_code = self.stage_code_generation();
+    pub fn compile(self) -> Result<CompiledScheme, CompileError> {
+        self.run(None)
+    }
 
-        CompiledScheme {
+    /// Like `compile`, additionally timing each stage.
+    pub fn compile_with_report(self) -> Result<(CompiledScheme, CompilationReport), CompileError> {
+        let mut report = CompilationReport::default();
+        let compiled = self.run(Some(&mut report))?;
+        Ok((compiled, report))
+    }
+
+    /// The stage sequence shared by `compile` and `compile_with_report`;
+    /// stage durations are recorded into `report` when given.
+    fn run(
+        self,
+        mut report: Option<&mut CompilationReport>,
+    ) -> Result<CompiledScheme, CompileError> {
+        fn timed<T>(slot: Option<&mut Duration>, stage: impl FnOnce() -> T) -> T {
+            let start = Instant::now();
+            let result = stage();
+            if let Some(slot) = slot {
+                *slot = start.elapsed();
+            }
+            result
+        }
+
+        let parallel_layers = timed(
+            report.as_deref_mut().map(|r| &mut r.structural_analysis),
+            || self.stage_structural_analysis(),
+        )?;
+        let (mut addresses, unmapped_segments) = timed(
+            report.as_deref_mut().map(|r| &mut r.memory_resolution),
+            || self.stage_memory_layout_resolution(),
+        );
+        let hardware_placement = timed(
+            report.as_deref_mut().map(|r| &mut r.hardware_mapping),
+            || -> Result<_, CompileError> {
+                let placement = self.stage_hardware_mapping(&addresses)?;
+                if self.partition_spaces {
+                    assign_address_spaces(&mut addresses, &placement);
+                }
+                Ok(placement)
+            },
+        )?;
+        let observation_code = timed(report.map(|r| &mut r.code_generation), || {
+            self.stage_code_generation(&addresses, &hardware_placement)
+        });
+
+        Ok(CompiledScheme {
             scheme: self.scheme,
             logical_addresses: addresses,
+            unmapped_segments,
+            parallel_layers,
             hardware_placement,
             observation_code,
+        })
+    }
+
+    /// Like `compile`, but returns a clone of the cached result when this
+    /// Scheme was already compiled for the same profile.
+    pub fn compile_cached(
+        self,
+        cache: &mut CompilationCache,
+    ) -> Result<CompiledScheme, CompileError> {
+        let key = CacheKey::of(&self);
+        if let Some(compiled) = cache.entries.get(&key) {
+            return Ok(compiled.clone());
         }
+        let compiled = self.compile()?;
+        cache.entries.insert(key, compiled.clone());
+        Ok(compiled)
+    }
+
+    /// Stage 2: Structural Analysis.
+    /// Groups Segments into the topological layers of their `Dependency`
+    /// relations (see `Scheme::dependency_layers`).
+    fn stage_structural_analysis(&self) -> Result<Vec<Vec<SegmentId>>, CompileError> {
+        self.scheme
+            .dependency_layers()
+            .map_err(|err| CompileError::CyclicDependency(err.unresolved))
     }
 
     /// Stage 3: Memory‑Layout Resolution.
     /// Uses the Scheme's `MemoryLayout` to compute a logical address for each Segment.
-    fn stage_memory_layout_resolution(&self) -> HashMap<SegmentId, LogicalAddress> {
+    /// Segments the layout rejects are returned separately instead of being dropped.
+    /// With compaction enabled the offsets are then renumbered densely.
+    fn stage_memory_layout_resolution(
+        &self,
+    ) -> (HashMap<SegmentId, LogicalAddress>, Vec<SegmentId>) {
         let mut addresses = HashMap::new();
+        let mut unmapped = Vec::new();
         for segment in self.scheme.segments() {
             let coords = segment.coordinates();
-            if let Some(addr) = self.scheme.map_to_logical_address(coords) {
-                addresses.insert(*segment.id(), addr);
+            match self.scheme.map_to_logical_address(coords) {
+                Some(addr) => {
+                    addresses.insert(*segment.id(), addr);
+                }
+                None => unmapped.push(*segment.id()),
             }
         }
-        addresses
+        unmapped.sort();
+        if self.compact {
+            compact_addresses(&mut addresses);
+        }
+        (addresses, unmapped)
     }
 
     /// Stage 4: Hardware Mapping.
@@ -98,21 +453,44 @@ impl CompilerPipeline {
     fn stage_hardware_mapping(
         &self,
         addresses: &HashMap<SegmentId, LogicalAddress>,
-    ) -> HashMap<SegmentId, HardwareResource> {
+    ) -> Result<HashMap<SegmentId, HardwareResource>, CompileError> {
         let mut placement = HashMap::new();
         match &self.profile {
-            HardwareProfile::Cpu { cores } => {
-                // Simple round‑robin assignment across cores.
-                for (idx, (segment_id, _)) in addresses.iter().enumerate() {
-                    let core = idx % cores;
+            HardwareProfile::Cpu { cores, numa_nodes } => {
+                if *cores == 0 || *numa_nodes == 0 || numa_nodes > cores {
+                    return Err(CompileError::InvalidCpuProfile {
+                        cores: *cores,
+                        numa_nodes: *numa_nodes,
+                    });
+                }
+                // Balanced block assignment in logical‑address order: neighbouring
+                // Segments share a core, block sizes differ by at most one, and
+                // since cores are numbered node‑major, neighbouring blocks share a
+                // NUMA node.
+                let mut ordered: Vec<_> = addresses.iter().collect();
+                ordered.sort_by_key(|(id, addr)| (addr.offset, **id));
+                let total = ordered.len();
+                for (idx, (segment_id, _)) in ordered.into_iter().enumerate() {
+                    let core = (idx as u128 * *cores as u128 / total as u128) as usize;
                     placement.insert(*segment_id, HardwareResource::CpuCore(core));
                 }
             }
-            HardwareProfile::Fpga { clbs } => {
-                // Place each Segment in a separate CLB (simplistic).
-                for (idx, (segment_id, _)) in addresses.iter().enumerate() {
-                    let clb = idx % clbs;
-                    placement.insert(*segment_id, HardwareResource::FpgaClb(clb));
+            HardwareProfile::Fpga { clbs, capacity } => {
+                // Dense packing in logical‑address order: fill CLB 0 to capacity,
+                // then CLB 1, and so on.
+                let total = clbs.saturating_mul(*capacity);
+                if addresses.len() > total {
+                    return Err(CompileError::FpgaCapacityExceeded {
+                        segments: addresses.len(),
+                        clbs: *clbs,
+                        capacity: *capacity,
+                        unplaced: addresses.len() - total,
+                    });
+                }
+                let mut ordered: Vec<_> = addresses.iter().collect();
+                ordered.sort_by_key(|(id, addr)| (addr.offset, **id));
+                for (idx, (segment_id, _)) in ordered.into_iter().enumerate() {
+                    placement.insert(*segment_id, HardwareResource::FpgaClb(idx / capacity));
                 }
             }
             HardwareProfile::Pim { units } => {
@@ -121,18 +499,615 @@ impl CompilerPipeline {
                     placement.insert(*segment_id, HardwareResource::PimUnit(unit));
                 }
             }
-            HardwareProfile::Custom(_) => {
-                // No mapping.
+            HardwareProfile::Gpu { sms, warp_size } => {
+                // Warp assignment in logical‑address order: each run of `warp_size`
+                // consecutive Segments forms one warp (coalesced access), and warps
+                // are dealt round‑robin over the SMs.
+                if *sms == 0 || *warp_size == 0 {
+                    return Err(CompileError::InvalidGpuProfile {
+                        sms: *sms,
+                        warp_size: *warp_size,
+                    });
+                }
+                let mut ordered: Vec<_> = addresses.iter().collect();
+                ordered.sort_by_key(|(id, addr)| (addr.offset, **id));
+                for (idx, (segment_id, _)) in ordered.into_iter().enumerate() {
+                    let resource = HardwareResource::GpuLane {
+                        sm: (idx / warp_size) % sms,
+                        lane: idx % warp_size,
+                    };
+                    placement.insert(*segment_id, resource);
+                }
+            }
+            HardwareProfile::Custom { mapper, .. } => {
+                for (segment_id, addr) in addresses {
+                    if let Some(segment) = self.scheme.get_segment(segment_id) {
+                        placement.insert(*segment_id, mapper(segment, addr));
+                    }
+                }
             }
         }
-        placement
+        Ok(placement)
     }
 
     /// Stage 5: Observation‑Code Generation.
-    /// Generates placeholder code (in reality this would produce machine code,
-    /// FPGA bitstream, or PIM micro‑code).
-    fn stage_code_generation(&self) -> Vec<u8> {
-        // Placeholder: empty byte vector.
-        vec![]
+    /// Emits one `ObservationRecord` per placed Segment, ordered by logical
+    /// offset (ties broken by SegmentId) so the output is deterministic.
+    /// In reality this would produce machine code, FPGA bitstream, or PIM micro‑code.
+    fn stage_code_generation(
+        &self,
+        addresses: &HashMap<SegmentId, LogicalAddress>,
+        placement: &HashMap<SegmentId, HardwareResource>,
+    ) -> Vec<u8> {
+        let mut placed: Vec<_> = placement
+            .iter()
+            .filter_map(|(id, resource)| addresses.get(id).map(|addr| (addr.offset, id, resource)))
+            .collect();
+        placed.sort_by_key(|(offset, id, _)| (*offset, **id));
+
+        let mut code = Vec::with_capacity(placed.len() * ObservationRecord::SIZE);
+        for (offset, _, resource) in placed {
+            let record = ObservationRecord {
+                resource: resource.clone(),
+                offset,
+            };
+            code.extend_from_slice(&record.encode());
+        }
+        code
+    }
+}
+
+/// Renumbers offsets densely to `0..n`, in ascending order of the original
+/// offset (ties broken by SegmentId), so relative order is preserved.
+/// Space ids and metadata are left untouched.
+pub fn compact_addresses(addresses: &mut HashMap<SegmentId, LogicalAddress>) {
+    let mut ordered: Vec<(u64, SegmentId)> = addresses
+        .iter()
+        .map(|(id, addr)| (addr.offset, *id))
+        .collect();
+    ordered.sort();
+    for (offset, (_, id)) in ordered.into_iter().enumerate() {
+        if let Some(addr) = addresses.get_mut(&id) {
+            addr.offset = offset as u64;
+        }
+    }
+}
+
+/// Sets each placed Segment's `space_id` to the index of its hardware resource
+/// (see `HardwareResource::index`). Unplaced Segments keep their space.
+pub fn assign_address_spaces(
+    addresses: &mut HashMap<SegmentId, LogicalAddress>,
+    placement: &HashMap<SegmentId, HardwareResource>,
+) {
+    for (id, addr) in addresses.iter_mut() {
+        if let Some(resource) = placement.get(id) {
+            addr.space_id = resource.index();
+        }
+    }
+}
+
+// ==================== OBSERVATION CODE ====================
+
+/// Opcode for "observe the Segment at `offset` on `resource`".
+pub const OP_OBSERVE: u8 = 0x01;
+
+/// A single instruction of the generated observation code.
+///
+/// Layout (little endian, fixed size):
+/// `opcode: u8 | resource_kind: u8 | resource_idx: u64 | resource_sub: u64 | offset: u64`
+///
+/// For GPU lanes `resource_idx` is the SM and `resource_sub` the lane; other
+/// resources leave `resource_sub` zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObservationRecord {
+    pub resource: HardwareResource,
+    pub offset: u64,
+}
+
+impl ObservationRecord {
+    /// Encoded size of one record in bytes.
+    pub const SIZE: usize = 1 + 1 + 8 + 8 + 8;
+
+    pub fn encode(&self) -> [u8; Self::SIZE] {
+        let (kind, idx, sub) = match self.resource {
+            HardwareResource::CpuCore(i) => (0u8, i as u64, 0),
+            HardwareResource::FpgaClb(i) => (1u8, i as u64, 0),
+            HardwareResource::PimUnit(i) => (2u8, i as u64, 0),
+            HardwareResource::GpuLane { sm, lane } => (3u8, sm as u64, lane as u64),
+            HardwareResource::Custom(id) => (4u8, id, 0),
+        };
+        let mut buf = [0u8; Self::SIZE];
+        buf[0] = OP_OBSERVE;
+        buf[1] = kind;
+        buf[2..10].copy_from_slice(&idx.to_le_bytes());
+        buf[10..18].copy_from_slice(&sub.to_le_bytes());
+        buf[18..26].copy_from_slice(&self.offset.to_le_bytes());
+        buf
+    }
+
+    /// Decodes a record from the start of `bytes`.
+    /// Returns `None` if the slice is too short, the opcode/kind is unknown, or
+    /// an index does not fit a `usize` on this platform.
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let bytes = bytes.get(..Self::SIZE)?;
+        if bytes[0] != OP_OBSERVE {
+            return None;
+        }
+        let idx = u64::from_le_bytes(bytes[2..10].try_into().ok()?);
+        let sub = u64::from_le_bytes(bytes[10..18].try_into().ok()?);
+        let to_usize = |v: u64| usize::try_from(v).ok();
+        let resource = match bytes[1] {
+            0 => HardwareResource::CpuCore(to_usize(idx)?),
+            1 => HardwareResource::FpgaClb(to_usize(idx)?),
+            2 => HardwareResource::PimUnit(to_usize(idx)?),
+            3 => HardwareResource::GpuLane {
+                sm: to_usize(idx)?,
+                lane: to_usize(sub)?,
+            },
+            4 => HardwareResource::Custom(idx),
+            _ => return None,
+        };
+        let offset = u64::from_le_bytes(bytes[18..26].try_into().ok()?);
+        Some(Self { resource, offset })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Segment;
+    use crate::scheme::abstract_scheme::integer_line::IntegerLineTemplate;
+    use crate::scheme::abstract_scheme::{
+        Axis, AxisType, DependencyType, LayoutType, MemoryLayout, SchemeBuilder, StructuralRelation,
+    };
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    fn line_with_dependencies(values: &[i64], deps: &[(i64, i64)]) -> Scheme {
+        let mut builder = SchemeBuilder::new()
+            .add_axis(Axis {
+                name: "x".to_string(),
+                axis_type: AxisType::Discrete,
+                metadata: HashMap::new(),
+            })
+            .add_segments(values.iter().map(|&v| Segment::from_value(v)));
+        for &(from, to) in deps {
+            let dependent = *Segment::from_value(from).id();
+            builder = builder.add_relation(
+                dependent,
+                *Segment::from_value(to).id(),
+                StructuralRelation::Dependency {
+                    dependent,
+                    dependency_type: DependencyType::DataFlow,
+                    strength: 1.0,
+                },
+            );
+        }
+        builder.build()
+    }
+
+    fn cpu(cores: usize) -> HardwareProfile {
+        HardwareProfile::Cpu {
+            cores,
+            numa_nodes: 1,
+        }
+    }
+
+    #[test]
+    fn test_code_generation_records() {
+        let scheme = IntegerLineTemplate::new(0, 9, 1).build();
+        let compiled = CompilerPipeline::new(scheme, cpu(4)).compile().unwrap();
+
+        let code = &compiled.observation_code;
+        assert_eq!(
+            code.len(),
+            compiled.hardware_placement.len() * ObservationRecord::SIZE
+        );
+
+        // Records are ordered by offset; the line starts at value 0.
+        let first = ObservationRecord::decode(code).expect("first record decodes");
+        assert_eq!(first.offset, 0);
+        assert_eq!(ObservationRecord::decode(&first.encode()), Some(first));
+
+        // The full index range survives the round trip.
+        let wide = ObservationRecord {
+            resource: HardwareResource::CpuCore(usize::MAX),
+            offset: u64::MAX,
+        };
+        assert_eq!(ObservationRecord::decode(&wide.encode()), Some(wide));
+    }
+
+    #[test]
+    fn test_cpu_placement_keeps_neighbours_together() {
+        let scheme = IntegerLineTemplate::new(0, 7, 1).build();
+        let profile = HardwareProfile::Cpu {
+            cores: 4,
+            numa_nodes: 2,
+        };
+        let compiled = CompilerPipeline::new(scheme, profile.clone())
+            .compile()
+            .unwrap();
+
+        let mut by_offset: Vec<_> = compiled
+            .logical_addresses
+            .iter()
+            .map(|(id, addr)| (addr.offset, compiled.hardware_placement[id].clone()))
+            .collect();
+        by_offset.sort_by_key(|(offset, _)| *offset);
+
+        // 8 Segments over 4 cores: blocks of two consecutive addresses.
+        for pair in by_offset.chunks(2) {
+            assert_eq!(pair[0].1, pair[1].1);
+        }
+        assert_eq!(by_offset[0].1, HardwareResource::CpuCore(0));
+        assert_eq!(by_offset[7].1, HardwareResource::CpuCore(3));
+        assert_eq!(profile.numa_node_of(1), Some(0));
+        assert_eq!(profile.numa_node_of(2), Some(1));
+    }
+
+    #[test]
+    fn test_cpu_placement_fills_every_numa_node() {
+        // 6 cores over 4 nodes: nodes hold 2, 2, 1 and 1 cores.
+        let profile = HardwareProfile::Cpu {
+            cores: 6,
+            numa_nodes: 4,
+        };
+        let nodes: Vec<_> = (0..6).map(|c| profile.numa_node_of(c).unwrap()).collect();
+        assert_eq!(nodes, vec![0, 0, 1, 1, 2, 3]);
+        assert_eq!(profile.numa_node_of(6), None);
+
+        let scheme = IntegerLineTemplate::new(0, 8, 1).build();
+        let compiled = CompilerPipeline::new(scheme, profile.clone())
+            .compile()
+            .unwrap();
+        let mut by_offset: Vec<_> = compiled
+            .logical_addresses
+            .iter()
+            .map(|(id, addr)| {
+                (
+                    addr.offset,
+                    compiled.hardware_placement[id].index() as usize,
+                )
+            })
+            .collect();
+        by_offset.sort();
+        let placed_nodes: Vec<_> = by_offset
+            .iter()
+            .map(|(_, core)| profile.numa_node_of(*core).unwrap())
+            .collect();
+
+        // Adjacent blocks stay on the same node or move to the next one.
+        assert!(
+            placed_nodes
+                .windows(2)
+                .all(|w| w[0] <= w[1] && w[1] - w[0] <= 1)
+        );
+        let used: HashSet<_> = placed_nodes.into_iter().collect();
+        assert_eq!(used.len(), 4);
+    }
+
+    #[test]
+    fn test_cpu_profile_rejects_empty_hardware() {
+        for (cores, numa_nodes) in [(0, 1), (4, 0), (2, 3)] {
+            let scheme = IntegerLineTemplate::new(0, 3, 1).build();
+            let profile = HardwareProfile::Cpu { cores, numa_nodes };
+            assert!(matches!(
+                CompilerPipeline::new(scheme, profile).compile(),
+                Err(CompileError::InvalidCpuProfile { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_unmapped_segments_are_reported() {
+        // Layout that only maps non‑negative coordinates.
+        let scheme = SchemeBuilder::new()
+            .add_axis(Axis {
+                name: "x".to_string(),
+                axis_type: AxisType::Discrete,
+                metadata: HashMap::new(),
+            })
+            .add_segments((-2..=2).map(Segment::from_value))
+            .set_memory_layout(MemoryLayout {
+                layout_type: LayoutType::Linear,
+                mapping: Arc::new(|coords| {
+                    let v = coords.get_axis(0)?;
+                    (v >= 0).then(|| LogicalAddress {
+                        space_id: 0,
+                        offset: v as u64,
+                        metadata: HashMap::new(),
+                    })
+                }),
+                metadata: HashMap::new(),
+            })
+            .build();
+
+        let compiled = CompilerPipeline::new(scheme, cpu(2)).compile().unwrap();
+        let mut expected = vec![*Segment::from_value(-2).id(), *Segment::from_value(-1).id()];
+        expected.sort();
+        assert_eq!(compiled.unmapped_segments, expected);
+        assert_eq!(compiled.hardware_placement.len(), 3);
+        assert!(!compiled.is_complete());
+
+        let full = CompilerPipeline::new(IntegerLineTemplate::new(0, 4, 1).build(), cpu(2))
+            .compile()
+            .unwrap();
+        assert!(full.is_complete());
+    }
+
+    #[test]
+    fn test_fpga_packs_clbs_densely() {
+        let scheme = IntegerLineTemplate::new(0, 4, 1).build();
+        let profile = HardwareProfile::Fpga {
+            clbs: 3,
+            capacity: 2,
+        };
+        let compiled = CompilerPipeline::new(scheme, profile).compile().unwrap();
+
+        let mut per_clb = HashMap::new();
+        for resource in compiled.hardware_placement.values() {
+            *per_clb.entry(resource.clone()).or_insert(0) += 1;
+        }
+        assert_eq!(per_clb[&HardwareResource::FpgaClb(0)], 2);
+        assert_eq!(per_clb[&HardwareResource::FpgaClb(1)], 2);
+        assert_eq!(per_clb[&HardwareResource::FpgaClb(2)], 1);
+    }
+
+    #[test]
+    fn test_gpu_profile_rejects_empty_hardware() {
+        for (sms, warp_size) in [(0, 32), (4, 0), (0, 0)] {
+            let scheme = IntegerLineTemplate::new(0, 3, 1).build();
+            let profile = HardwareProfile::Gpu { sms, warp_size };
+            assert!(matches!(
+                CompilerPipeline::new(scheme, profile).compile(),
+                Err(CompileError::InvalidGpuProfile { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_gpu_warps_share_an_sm() {
+        let scheme = IntegerLineTemplate::new(0, 15, 1).build();
+        let profile = HardwareProfile::Gpu {
+            sms: 2,
+            warp_size: 4,
+        };
+        let compiled = CompilerPipeline::new(scheme, profile).compile().unwrap();
+        let lane_of = |v: i64| compiled.hardware_placement[Segment::from_value(v).id()].clone();
+
+        let first_warp: Vec<_> = (0..4).map(lane_of).collect();
+        for (lane, resource) in first_warp.iter().enumerate() {
+            assert_eq!(*resource, HardwareResource::GpuLane { sm: 0, lane });
+        }
+        assert_eq!(lane_of(4), HardwareResource::GpuLane { sm: 1, lane: 0 });
+        assert_eq!(lane_of(8), HardwareResource::GpuLane { sm: 0, lane: 0 });
+
+        let record = ObservationRecord {
+            resource: HardwareResource::GpuLane { sm: 3, lane: 31 },
+            offset: 9,
+        };
+        assert_eq!(ObservationRecord::decode(&record.encode()), Some(record));
+        let wide = ObservationRecord {
+            resource: HardwareResource::GpuLane {
+                sm: 70_000,
+                lane: 1 << 20,
+            },
+            offset: 9,
+        };
+        assert_eq!(ObservationRecord::decode(&wide.encode()), Some(wide));
+    }
+
+    #[test]
+    fn test_custom_mapper_places_every_segment() {
+        let scheme = IntegerLineTemplate::new(0, 9, 1).build();
+        let profile = HardwareProfile::Custom {
+            name: "seven".to_string(),
+            mapper: Arc::new(|_, _| HardwareResource::Custom(7)),
+        };
+        let compiled = CompilerPipeline::new(scheme, profile).compile().unwrap();
+
+        assert!(compiled.is_complete());
+        assert!(
+            compiled
+                .hardware_placement
+                .values()
+                .all(|r| *r == HardwareResource::Custom(7))
+        );
+        let first = ObservationRecord::decode(&compiled.observation_code).unwrap();
+        assert_eq!(first.resource, HardwareResource::Custom(7));
+
+        // Ids beyond 32 bits stay distinct in the encoded records.
+        let high = ObservationRecord {
+            resource: HardwareResource::Custom(u64::MAX),
+            offset: 0,
+        };
+        let low = ObservationRecord {
+            resource: HardwareResource::Custom(u32::MAX as u64),
+            offset: 0,
+        };
+        assert_ne!(high.encode(), low.encode());
+        assert_eq!(ObservationRecord::decode(&high.encode()), Some(high));
+    }
+
+    #[test]
+    fn test_compaction_removes_gaps() {
+        let sparse = IntegerLineTemplate::new(0, 90, 10).build();
+        let plain = CompilerPipeline::new(sparse.clone(), cpu(2))
+            .compile()
+            .unwrap();
+        let compacted = CompilerPipeline::new(sparse, cpu(2))
+            .with_compaction(true)
+            .compile()
+            .unwrap();
+
+        let max_offset = plain.logical_addresses.values().map(|a| a.offset).max();
+        assert_eq!(max_offset, Some(90));
+
+        let mut offsets: Vec<u64> = compacted
+            .logical_addresses
+            .values()
+            .map(|a| a.offset)
+            .collect();
+        offsets.sort();
+        assert_eq!(offsets, (0..10).collect::<Vec<u64>>());
+
+        // Relative order is unchanged.
+        let order = |c: &CompiledScheme| {
+            let mut ids: Vec<_> = c.logical_addresses.iter().collect();
+            ids.sort_by_key(|(_, a)| a.offset);
+            ids.into_iter().map(|(id, _)| *id).collect::<Vec<_>>()
+        };
+        assert_eq!(order(&plain), order(&compacted));
+    }
+
+    #[test]
+    fn test_address_spaces_follow_cores() {
+        let scheme = IntegerLineTemplate::new(0, 7, 1).build();
+        let compiled = CompilerPipeline::new(scheme, cpu(4))
+            .with_address_spaces(true)
+            .compile()
+            .unwrap();
+
+        let on_core_2: Vec<_> = compiled
+            .hardware_placement
+            .iter()
+            .filter(|(_, r)| **r == HardwareResource::CpuCore(2))
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(on_core_2.len(), 2);
+        for id in on_core_2 {
+            assert_eq!(compiled.logical_addresses[id].space_id, 2);
+        }
+        for (id, addr) in &compiled.logical_addresses {
+            assert_eq!(addr.space_id, compiled.hardware_placement[id].index());
+        }
+    }
+
+    #[test]
+    fn test_fpga_capacity_exceeded() {
+        let scheme = IntegerLineTemplate::new(0, 9, 1).build();
+        let profile = HardwareProfile::Fpga {
+            clbs: 2,
+            capacity: 3,
+        };
+        let result = CompilerPipeline::new(scheme, profile).compile();
+        assert!(matches!(
+            result,
+            Err(CompileError::FpgaCapacityExceeded {
+                segments: 10,
+                unplaced: 4,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_structural_analysis_layers() {
+        // 2 depends on 0 and 1
+        let scheme = line_with_dependencies(&[0, 1, 2], &[(2, 0), (2, 1)]);
+        let compiled = CompilerPipeline::new(scheme, cpu(2)).compile().unwrap();
+
+        let mut first = vec![*Segment::from_value(0).id(), *Segment::from_value(1).id()];
+        first.sort();
+        assert_eq!(
+            compiled.parallel_layers,
+            vec![first, vec![*Segment::from_value(2).id()]]
+        );
+    }
+
+    #[test]
+    fn test_compile_cached_reuses_results() {
+        let mut cache = CompilationCache::new();
+        let scheme = IntegerLineTemplate::new(0, 9, 1).build();
+
+        let first = CompilerPipeline::new(scheme.clone(), cpu(4))
+            .compile_cached(&mut cache)
+            .unwrap();
+        assert_eq!(cache.len(), 1);
+        let second = CompilerPipeline::new(scheme.clone(), cpu(4))
+            .compile_cached(&mut cache)
+            .unwrap();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(first.hardware_placement, second.hardware_placement);
+        assert_eq!(first.observation_code, second.observation_code);
+
+        // A different profile is a separate entry.
+        CompilerPipeline::new(scheme, cpu(2))
+            .compile_cached(&mut cache)
+            .unwrap();
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_compile_cached_distinguishes_custom_mappers() {
+        let mut cache = CompilationCache::new();
+        let scheme = IntegerLineTemplate::new(0, 3, 1).build();
+        let custom = |mapper: PlacementFn| HardwareProfile::Custom {
+            name: "same".to_string(),
+            mapper,
+        };
+        let on_one: PlacementFn = Arc::new(|_, _| HardwareResource::Custom(1));
+        let on_two: PlacementFn = Arc::new(|_, _| HardwareResource::Custom(2));
+
+        let first = CompilerPipeline::new(scheme.clone(), custom(on_one.clone()))
+            .compile_cached(&mut cache)
+            .unwrap();
+        let second = CompilerPipeline::new(scheme.clone(), custom(on_two))
+            .compile_cached(&mut cache)
+            .unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(
+            first
+                .hardware_placement
+                .values()
+                .all(|r| *r == HardwareResource::Custom(1))
+        );
+        assert!(
+            second
+                .hardware_placement
+                .values()
+                .all(|r| *r == HardwareResource::Custom(2))
+        );
+
+        // A clone of the same mapper hits the existing entry.
+        CompilerPipeline::new(scheme, custom(on_one))
+            .compile_cached(&mut cache)
+            .unwrap();
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_compile_with_report_times_every_stage() {
+        let scheme = IntegerLineTemplate::new(0, 99, 1).build();
+        let started = Instant::now();
+        let (compiled, report) = CompilerPipeline::new(scheme.clone(), cpu(4))
+            .compile_with_report()
+            .unwrap();
+        let wall = started.elapsed();
+        let plain = CompilerPipeline::new(scheme, cpu(4)).compile().unwrap();
+        assert_eq!(compiled.observation_code, plain.observation_code);
+
+        let stages = report.stages();
+        let names: Vec<_> = stages.iter().map(|(name, _)| *name).collect();
+        assert_eq!(
+            names,
+            [
+                "structural_analysis",
+                "memory_resolution",
+                "hardware_mapping",
+                "code_generation"
+            ]
+        );
+        let sum: Duration = stages.iter().map(|(_, d)| *d).sum();
+        assert_eq!(report.total(), sum);
+        // Stages are timed inside the call, so together they cannot exceed it.
+        assert!(sum <= wall);
+    }
+
+    #[test]
+    fn test_structural_analysis_rejects_cycles() {
+        let scheme = line_with_dependencies(&[0, 1, 2], &[(0, 1), (1, 0)]);
+        let result = CompilerPipeline::new(scheme, cpu(2)).compile();
+        assert!(matches!(result, Err(CompileError::CyclicDependency(ids)) if ids.len() == 2));
     }
 }
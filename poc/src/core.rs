@@ -6,7 +6,9 @@
 //! - `Projector` trait for semantic interpretation
 //! - Observation functions that combine segment and field
 
-use std::collections::HashMap;
+use crate::scheme::abstract_scheme::Scheme;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::sync::Arc;
@@ -14,7 +16,8 @@ use std::sync::Arc;
 // ==================== CORE TYPES ====================
 
 /// A coordinate in an abstract space. All axes are equivalent.
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+/// Ordering is lexicographic over the raw axis values.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct SpaceCoordinates {
     pub raw: Vec<i64>,
 }
@@ -31,6 +34,90 @@ impl SpaceCoordinates {
     pub fn get_axis(&self, axis: usize) -> Option<i64> {
         self.raw.get(axis).copied()
     }
+
+    /// Copy with exactly `dim` axes: extra axes are truncated, missing ones are zero.
+    pub fn normalized_to(&self, dim: usize) -> SpaceCoordinates {
+        let mut raw = self.raw.clone();
+        raw.resize(dim, 0);
+        Self { raw }
+    }
+
+    /// The common dimensionality of `coords`, or `None` if they are ragged
+    /// (or the slice is empty).
+    pub fn is_uniform_dim(coords: &[SpaceCoordinates]) -> Option<usize> {
+        let dim = coords.first()?.dimensionality();
+        coords
+            .iter()
+            .all(|c| c.dimensionality() == dim)
+            .then_some(dim)
+    }
+
+    /// Copy with `axis` set to `value`, growing with zeros if `axis` is beyond the end.
+    pub fn with_axis(&self, axis: usize, value: i64) -> SpaceCoordinates {
+        let mut raw = self.raw.clone();
+        if axis >= raw.len() {
+            raw.resize(axis + 1, 0);
+        }
+        raw[axis] = value;
+        Self { raw }
+    }
+
+    /// Element‑wise saturating sum. The shorter operand is padded with zeros,
+    /// so the result has the dimensionality of the longer one.
+    pub fn add(&self, other: &SpaceCoordinates) -> SpaceCoordinates {
+        let len = self.raw.len().max(other.raw.len());
+        let raw = (0..len)
+            .map(|i| {
+                self.get_axis(i)
+                    .unwrap_or(0)
+                    .saturating_add(other.get_axis(i).unwrap_or(0))
+            })
+            .collect();
+        Self { raw }
+    }
+
+    /// Multiply every axis by `factor`, saturating at the `i64` bounds.
+    pub fn scale(&self, factor: i64) -> SpaceCoordinates {
+        Self {
+            raw: self.raw.iter().map(|v| v.saturating_mul(factor)).collect(),
+        }
+    }
+
+    /// Sum of per‑axis absolute differences, padding the shorter operand with
+    /// zeros. Saturates at `i64::MAX`.
+    pub fn manhattan_distance(&self, other: &SpaceCoordinates) -> i64 {
+        let len = self.raw.len().max(other.raw.len());
+        let total = (0..len).fold(0u64, |acc, i| {
+            let diff = self
+                .get_axis(i)
+                .unwrap_or(0)
+                .abs_diff(other.get_axis(i).unwrap_or(0));
+            acc.saturating_add(diff)
+        });
+        i64::try_from(total).unwrap_or(i64::MAX)
+    }
+}
+
+/// Distance function between coordinates. Operands of different
+/// dimensionality are compared as if the shorter were padded with zeros.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Metric {
+    Manhattan,
+    Euclidean,
+    Chebyshev,
+}
+
+impl Metric {
+    pub fn distance(&self, a: &SpaceCoordinates, b: &SpaceCoordinates) -> f64 {
+        let len = a.raw.len().max(b.raw.len());
+        let diffs = (0..len)
+            .map(|i| (a.get_axis(i).unwrap_or(0) as f64 - b.get_axis(i).unwrap_or(0) as f64).abs());
+        match self {
+            Metric::Manhattan => diffs.sum(),
+            Metric::Euclidean => diffs.map(|d| d * d).sum::<f64>().sqrt(),
+            Metric::Chebyshev => diffs.fold(0.0, f64::max),
+        }
+    }
 }
 
 /// Cryptographic identifier of a SchemaSegment.
@@ -54,16 +141,51 @@ impl SegmentId {
     pub fn as_bytes(&self) -> &[u8; 32] {
         &self.0
     }
+
+    /// Parse the 64‑character hex form produced by `Display`.
+    pub fn from_hex(s: &str) -> Result<SegmentId, String> {
+        if s.len() != 64 {
+            return Err(format!(
+                "SegmentId hex must be 64 characters, got {}",
+                s.len()
+            ));
+        }
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(s, &mut bytes).map_err(|e| format!("Invalid SegmentId hex: {}", e))?;
+        Ok(SegmentId(bytes))
+    }
+}
+
+impl std::fmt::Display for SegmentId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
 }
 
 /// An immutable point in possibility space.
 /// Contains only coordinates and a cryptographic identity.
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+/// Equality and hashing use the id alone, which is the canonical identity;
+/// use `same_coordinates` to compare positions.
+#[derive(Clone, Debug)]
 pub struct Segment {
     coords: SpaceCoordinates,
     id: SegmentId,
 }
 
+impl PartialEq for Segment {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Segment {}
+
+impl Hash for Segment {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
 /// Compute SegmentId from coordinates (public helper function).
 pub fn segment_id_from_coords(coords: &SpaceCoordinates) -> SegmentId {
     let mut hasher = blake3::Hasher::new();
@@ -91,6 +213,16 @@ impl Segment {
         &self.id
     }
 
+    /// Whether both segments sit at the same coordinates (regardless of identity).
+    pub fn same_coordinates(&self, other: &Segment) -> bool {
+        self.coords == other.coords
+    }
+
+    /// Distance from this segment's coordinates to `coords` under `metric`.
+    pub fn distance_to(&self, coords: &SpaceCoordinates, metric: Metric) -> f64 {
+        metric.distance(&self.coords, coords)
+    }
+
     /// Create a Segment from a single value (convenience for 1D spaces).
     pub fn from_value(value: i64) -> Self {
         Self::new(SpaceCoordinates::new(vec![value]))
@@ -100,6 +232,19 @@ impl Segment {
     pub fn from_values(values: Vec<i64>) -> Self {
         Self::new(SpaceCoordinates::new(values))
     }
+
+    /// Create a Segment, rejecting coordinates whose dimensionality is not `expected_dim`.
+    pub fn try_new(coords: SpaceCoordinates, expected_dim: usize) -> Result<Self, String> {
+        if coords.dimensionality() != expected_dim {
+            return Err(format!(
+                "Coordinates {:?} have {} dimensions, expected {}",
+                coords.raw,
+                coords.dimensionality(),
+                expected_dim
+            ));
+        }
+        Ok(Self::new(coords))
+    }
 }
 
 /// A constraint on coordinates.
@@ -127,6 +272,23 @@ impl ConstraintSet {
         self.constraints.iter().all(|c| c.allows(coords))
     }
 
+    /// Descriptions of every constraint rejecting `coords`, in insertion order.
+    pub fn blocking(&self, coords: &SpaceCoordinates) -> Vec<String> {
+        self.constraints
+            .iter()
+            .filter(|c| !c.allows(coords))
+            .map(|c| c.describe())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.constraints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.constraints.is_empty()
+    }
+
     pub fn describe(&self) -> String {
         if self.constraints.is_empty() {
             "no constraints".into()
@@ -140,6 +302,32 @@ impl ConstraintSet {
     }
 }
 
+/// A constraint that is never enforced but costs `penalty` when violated.
+/// Used for optimisation‑style resolution rather than admissibility.
+#[derive(Debug, Clone)]
+pub struct SoftConstraint {
+    constraint: Arc<dyn Constraint>,
+    penalty: f64,
+}
+
+impl SoftConstraint {
+    pub fn new(constraint: impl Constraint + 'static, penalty: f64) -> Self {
+        Self {
+            constraint: Arc::new(constraint),
+            penalty,
+        }
+    }
+
+    /// Penalty incurred by `coords`: `penalty` if violated, otherwise 0.
+    pub fn cost(&self, coords: &SpaceCoordinates) -> f64 {
+        if self.constraint.allows(coords) {
+            0.0
+        } else {
+            self.penalty
+        }
+    }
+}
+
 /// Relational topology of the Field – currently a weighted directed graph.
 /// This is one possible representation; it may be generalised later.
 /// Uses SegmentId for relationship definitions to align with SSCCS cryptographic identity system.
@@ -211,6 +399,68 @@ impl TransitionMatrix {
             .and_then(|vec| vec.iter().find(|(t, _)| t == to).map(|(_, w)| *w))
     }
 
+    /// All transitions whose endpoint coordinates are known, as
+    /// `(from, to, weight)`, sorted by coordinates for deterministic output.
+    pub fn edges(&self) -> Vec<(SpaceCoordinates, SpaceCoordinates, f64)> {
+        let mut edges: Vec<_> = self
+            .edges
+            .iter()
+            .flat_map(|(from, targets)| targets.iter().map(move |(to, w)| (from, to, *w)))
+            .filter_map(|(from, to, w)| {
+                Some((
+                    self.id_to_coords.get(from)?.clone(),
+                    self.id_to_coords.get(to)?.clone(),
+                    w,
+                ))
+            })
+            .collect();
+        edges.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)).then(a.2.total_cmp(&b.2)));
+        edges
+    }
+
+    /// Graphviz rendering: one node per distinct segment, labeled with its raw
+    /// coordinates when known (else its id), and one edge per transition
+    /// labeled with its weight. Output is sorted for stable diffs.
+    pub fn to_dot(&self) -> String {
+        let label = |id: &SegmentId| match self.id_to_coords.get(id) {
+            Some(coords) => format!("{:?}", coords.raw),
+            None => id.to_string(),
+        };
+        let mut nodes: Vec<&SegmentId> = self
+            .edges
+            .iter()
+            .flat_map(|(from, targets)| {
+                std::iter::once(from).chain(targets.iter().map(|(to, _)| to))
+            })
+            .collect();
+        nodes.sort_by_key(|id| (label(id), **id));
+        nodes.dedup();
+
+        let mut edges: Vec<(&SegmentId, &SegmentId, f64)> = self
+            .edges
+            .iter()
+            .flat_map(|(from, targets)| targets.iter().map(move |(to, w)| (from, to, *w)))
+            .collect();
+        edges.sort_by(|a, b| {
+            (label(a.0), label(a.1))
+                .cmp(&(label(b.0), label(b.1)))
+                .then(a.2.total_cmp(&b.2))
+        });
+
+        let mut dot = String::from("digraph {\n");
+        for id in nodes {
+            dot.push_str(&format!("    \"{}\" [label=\"{}\"];\n", id, label(id)));
+        }
+        for (from, to, weight) in edges {
+            dot.push_str(&format!(
+                "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                from, to, weight
+            ));
+        }
+        dot.push('}');
+        dot
+    }
+
     /// Get weight between coordinates (legacy API).
     pub fn get_weight(&self, from: &SpaceCoordinates, to: &SpaceCoordinates) -> Option<f64> {
         let from_id = segment_id_from_coords(from);
@@ -224,6 +474,7 @@ impl TransitionMatrix {
 #[derive(Debug, Clone, Default)]
 pub struct Field {
     constraints: ConstraintSet,
+    soft_constraints: Vec<SoftConstraint>,
     transitions: TransitionMatrix,
 }
 
@@ -237,16 +488,43 @@ impl Field {
         self.constraints.add(constraint);
     }
 
+    /// Describe every constraint that rejects `coords` (empty if allowed).
+    pub fn blocking_constraints(&self, coords: &SpaceCoordinates) -> Vec<String> {
+        self.constraints.blocking(coords)
+    }
+
+    /// Add a soft constraint: violating it adds `penalty` instead of rejecting.
+    pub fn add_soft_constraint(&mut self, constraint: impl Constraint + 'static, penalty: f64) {
+        self.soft_constraints
+            .push(SoftConstraint::new(constraint, penalty));
+    }
+
+    /// Total penalty of the soft constraints `coords` violates.
+    /// Suitable as the energy function for energy‑minimising resolution.
+    pub fn penalty(&self, coords: &SpaceCoordinates) -> f64 {
+        self.soft_constraints.iter().map(|c| c.cost(coords)).sum()
+    }
+
     /// Add a transition rule (from → to with weight).
     pub fn add_transition(&mut self, from: SpaceCoordinates, to: SpaceCoordinates, weight: f64) {
         self.transitions.add(from, to, weight);
     }
 
+    /// Graphviz (DOT) rendering of the field's transitions.
+    pub fn to_dot(&self) -> String {
+        self.transitions.to_dot()
+    }
+
     /// Check whether a coordinate is allowed by all current constraints.
     pub fn allows(&self, coords: &SpaceCoordinates) -> bool {
         self.constraints.allows(coords)
     }
 
+    /// The field's relational topology.
+    pub fn transitions(&self) -> &TransitionMatrix {
+        &self.transitions
+    }
+
     /// Return all transition targets from a given coordinate (defined by the field only).
     pub fn transition_targets(&self, from: &SpaceCoordinates) -> Vec<SpaceCoordinates> {
         self.transitions.transitions_from(from)
@@ -256,6 +534,93 @@ impl Field {
     pub fn describe_constraints(&self) -> String {
         self.constraints.describe()
     }
+
+    /// Collapse a scheme through this field: the scheme's segments whose
+    /// coordinates the field allows, sorted by SegmentId
+    /// (same as `Scheme::segments_allowed_by`).
+    pub fn allowed_segments<'a>(&self, scheme: &'a Scheme) -> Vec<&'a Segment> {
+        scheme.segments_allowed_by(self)
+    }
+
+    /// Estimate the number of constraint evaluations needed to enumerate every
+    /// coordinate in `bounds` (inclusive `(min, max)` per axis): the box volume
+    /// times the constraint count (at least one check per coordinate).
+    /// Saturates at `u128::MAX`; callers can use it to bail out before a runaway scan.
+    pub fn complexity_hint(&self, bounds: &[(i64, i64)]) -> u128 {
+        box_volume(bounds).saturating_mul(self.constraints.len().max(1) as u128)
+    }
+
+    /// Count the coordinates in `bounds` (inclusive `(min, max)` per axis) the
+    /// field allows, without materializing them. Cost is one `allows` call per
+    /// coordinate in the box (see `complexity_hint`). An empty `bounds` slice
+    /// describes the zero-dimensional origin, which is checked once.
+    pub fn allowed_count_in_bounds(&self, bounds: &[(i64, i64)]) -> usize {
+        let mut count = 0;
+        for_each_in_bounds(bounds, |coords| {
+            if self.allows(coords) {
+                count += 1;
+            }
+        });
+        count
+    }
+
+    /// Fraction of coordinates in `bounds` the field allows, in `[0, 1]`.
+    /// An empty box (some `min > max`) has ratio 0.
+    pub fn satisfaction_ratio(&self, bounds: &[(i64, i64)]) -> f64 {
+        let total = box_volume(bounds);
+        if total == 0 {
+            return 0.0;
+        }
+        self.allowed_count_in_bounds(bounds) as f64 / total as f64
+    }
+
+    /// Like `allowed_count_in_bounds`, but collects the allowed coordinates
+    /// in lexicographic order.
+    pub fn allowed_coordinates_in_bounds(&self, bounds: &[(i64, i64)]) -> Vec<SpaceCoordinates> {
+        let mut allowed = Vec::new();
+        for_each_in_bounds(bounds, |coords| {
+            if self.allows(coords) {
+                allowed.push(coords.clone());
+            }
+        });
+        allowed
+    }
+
+    /// Lazily explore the states reachable from `start` in breadth‑first order,
+    /// yielding each allowed coordinate together with its projection.
+    /// Adjacency combines the projector's semantics and the field's transitions;
+    /// coordinates the field rejects are neither yielded nor expanded, and states
+    /// the projector cannot project are expanded but not yielded.
+    pub fn project_reachable<'a, P: Projector>(
+        &'a self,
+        start: SpaceCoordinates,
+        projector: &'a P,
+    ) -> impl Iterator<Item = (SpaceCoordinates, P::Output)> + 'a {
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::new();
+        if self.allows(&start) {
+            visited.insert(start.clone());
+            queue.push_back(start);
+        }
+
+        std::iter::from_fn(move || {
+            while let Some(current) = queue.pop_front() {
+                let mut next = projector.possible_next_coordinates(&current);
+                next.extend(self.transition_targets(&current));
+                for coords in next {
+                    if self.allows(&coords) && visited.insert(coords.clone()) {
+                        queue.push_back(coords);
+                    }
+                }
+
+                let segment = Segment::new(current);
+                if let Some(value) = projector.project(self, &segment) {
+                    return Some((segment.coords, value));
+                }
+            }
+            None
+        })
+    }
 }
 
 /// A projector gives semantic meang to a combination of Field and Segment.
@@ -267,10 +632,387 @@ pub trait Projector: Debug + Send + Sync {
     /// and the segment's intrinsic properties.
     fn project(&self, field: &Field, segment: &Segment) -> Option<Self::Output>;
 
+    /// Every projection of the segment, for inherently multi‑valued interpretations.
+    /// Defaults to the single result of `project` (or none).
+    fn project_all(&self, field: &Field, segment: &Segment) -> Vec<Self::Output> {
+        self.project(field, segment).into_iter().collect()
+    }
+
     /// Given a coordinate, return the possible next coordinates according to this projector's interpretation.
     /// This is where the projector defines the "adjacency" semantics (e.g., arithmetic operations, graph edges, etc.).
     /// The default implementation returns an empty vector, meaning no intrinsic adjacency.
     fn possible_next_coordinates(&self, _: &SpaceCoordinates) -> Vec<SpaceCoordinates> {
         Vec::new()
     }
+
+    /// Name of the output type, for logging and dynamic registries.
+    fn output_type_name(&self) -> &'static str {
+        std::any::type_name::<Self::Output>()
+    }
+
+    /// Human‑readable description (for debugging). Defaults to the type name.
+    fn describe(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
+}
+
+/// Memoizing wrapper around a `Field` for workloads that query the same
+/// coordinates repeatedly (e.g. tree generation). The wrapped field stays pure;
+/// the cache is cleared whenever a constraint is added through this wrapper.
+#[derive(Debug, Clone, Default)]
+pub struct CachingField {
+    field: Field,
+    cache: RefCell<HashMap<SpaceCoordinates, bool>>,
+}
+
+impl CachingField {
+    pub fn new(field: Field) -> Self {
+        Self {
+            field,
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn field(&self) -> &Field {
+        &self.field
+    }
+
+    /// Add a constraint to the wrapped field and invalidate the cache.
+    pub fn add_constraint(&mut self, constraint: impl Constraint + 'static) {
+        self.field.add_constraint(constraint);
+        self.cache.get_mut().clear();
+    }
+
+    pub fn allows(&self, coords: &SpaceCoordinates) -> bool {
+        if let Some(&allowed) = self.cache.borrow().get(coords) {
+            return allowed;
+        }
+        let allowed = self.field.allows(coords);
+        self.cache.borrow_mut().insert(coords.clone(), allowed);
+        allowed
+    }
+
+    /// Number of memoized coordinates.
+    pub fn cached_len(&self) -> usize {
+        self.cache.borrow().len()
+    }
+}
+
+/// Number of coordinates in the inclusive box `bounds`, saturating at `u128::MAX`.
+/// An empty slice is the zero-dimensional origin (volume 1).
+fn box_volume(bounds: &[(i64, i64)]) -> u128 {
+    bounds.iter().fold(1u128, |acc, &(min, max)| {
+        let size = (max as i128 - min as i128 + 1).max(0) as u128;
+        acc.saturating_mul(size)
+    })
+}
+
+/// Visit every coordinate of the inclusive box `bounds` in lexicographic order.
+fn for_each_in_bounds(bounds: &[(i64, i64)], mut f: impl FnMut(&SpaceCoordinates)) {
+    if bounds.iter().any(|&(min, max)| min > max) {
+        return;
+    }
+    let mut coords = SpaceCoordinates::new(bounds.iter().map(|&(min, _)| min).collect());
+    loop {
+        f(&coords);
+        // Odometer increment from the last axis.
+        let mut axis = bounds.len();
+        loop {
+            if axis == 0 {
+                return;
+            }
+            axis -= 1;
+            if coords.raw[axis] < bounds[axis].1 {
+                coords.raw[axis] += 1;
+                break;
+            }
+            coords.raw[axis] = bounds[axis].0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal arithmetic adjacency (+1, *2) on axis 0.
+    #[derive(Debug)]
+    struct StepProjector;
+
+    impl Projector for StepProjector {
+        type Output = i64;
+
+        fn project(&self, _field: &Field, segment: &Segment) -> Option<i64> {
+            segment.coordinates().get_axis(0)
+        }
+
+        fn possible_next_coordinates(&self, coords: &SpaceCoordinates) -> Vec<SpaceCoordinates> {
+            let v = coords.get_axis(0).unwrap_or(0);
+            vec![
+                SpaceCoordinates::new(vec![v + 1]),
+                SpaceCoordinates::new(vec![v * 2]),
+            ]
+        }
+    }
+
+    #[derive(Debug)]
+    struct AtMost(i64);
+
+    impl Constraint for AtMost {
+        fn allows(&self, coords: &SpaceCoordinates) -> bool {
+            coords.get_axis(0).is_some_and(|v| v <= self.0)
+        }
+
+        fn describe(&self) -> String {
+            format!("axis[0] <= {}", self.0)
+        }
+    }
+
+    #[test]
+    fn test_project_reachable_is_breadth_first() {
+        let mut field = Field::new();
+        field.add_constraint(AtMost(100));
+
+        let first: Vec<i64> = field
+            .project_reachable(SpaceCoordinates::new(vec![1]), &StepProjector)
+            .map(|(_, value)| value)
+            .take(4)
+            .collect();
+        // 1 → {2} → {3, 4} ...
+        assert_eq!(first, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_project_reachable_respects_constraints() {
+        let mut field = Field::new();
+        field.add_constraint(AtMost(5));
+
+        let all: Vec<_> = field
+            .project_reachable(SpaceCoordinates::new(vec![1]), &StepProjector)
+            .collect();
+        assert_eq!(all.len(), 5);
+        assert!(all.iter().all(|(coords, value)| coords.raw[0] == *value));
+        assert_eq!(
+            field
+                .project_reachable(SpaceCoordinates::new(vec![9]), &StepProjector)
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_complexity_hint_scales_with_box() {
+        let mut field = Field::new();
+        field.add_constraint(AtMost(10));
+        field.add_constraint(AtMost(20));
+
+        assert_eq!(field.complexity_hint(&[(0, 1), (0, 1)]), 8);
+        assert_eq!(field.complexity_hint(&[(5, 4)]), 0);
+        let large = field.complexity_hint(&[(0, 999_999), (0, 999_999), (0, 999_999)]);
+        assert_eq!(large, 2_000_000_000_000_000_000);
+        assert_eq!(field.complexity_hint(&[(i64::MIN, i64::MAX); 5]), u128::MAX);
+    }
+
+    #[test]
+    fn test_allowed_segments_of_grid() {
+        use crate::RangeConstraint;
+        use crate::scheme::{Grid2DTemplate, GridTopology};
+
+        let grid = Grid2DTemplate::new(4, 4, GridTopology::FourConnected).build();
+        let mut field = Field::new();
+        field.add_constraint(RangeConstraint::new(0, 1, 2));
+
+        let allowed = field.allowed_segments(&grid);
+        assert_eq!(allowed.len(), 8);
+        assert_eq!(allowed, grid.segments_allowed_by(&field));
+    }
+
+    #[test]
+    fn test_soft_constraint_penalty() {
+        let mut field = Field::new();
+        field.add_soft_constraint(AtMost(10), 2.5);
+        field.add_soft_constraint(AtMost(20), 4.0);
+
+        assert_eq!(field.penalty(&SpaceCoordinates::new(vec![5])), 0.0);
+        assert_eq!(field.penalty(&SpaceCoordinates::new(vec![15])), 2.5);
+        assert_eq!(field.penalty(&SpaceCoordinates::new(vec![25])), 6.5);
+        // Soft constraints never reject.
+        assert!(field.allows(&SpaceCoordinates::new(vec![25])));
+    }
+
+    #[test]
+    fn test_blocking_constraints_lists_all_failures() {
+        let mut field = Field::new();
+        field.add_constraint(AtMost(10));
+        field.add_constraint(AtMost(20));
+        field.add_constraint(AtMost(100));
+
+        assert_eq!(
+            field.blocking_constraints(&SpaceCoordinates::new(vec![50])),
+            vec!["axis[0] <= 10".to_string(), "axis[0] <= 20".to_string()]
+        );
+        assert!(
+            field
+                .blocking_constraints(&SpaceCoordinates::new(vec![5]))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_allowed_count_in_bounds() {
+        let mut field = Field::new();
+        field.add_constraint(crate::RangeConstraint::new(0, 2, 5));
+        field.add_constraint(crate::RangeConstraint::new(1, 0, 2));
+        let bounds = [(0, 9), (0, 9)];
+
+        assert_eq!(field.allowed_count_in_bounds(&bounds), 12);
+        let allowed = field.allowed_coordinates_in_bounds(&bounds);
+        assert_eq!(allowed.len(), 12);
+        assert_eq!(allowed[0], SpaceCoordinates::new(vec![2, 0]));
+        assert_eq!(allowed[11], SpaceCoordinates::new(vec![5, 2]));
+
+        assert_eq!(Field::new().allowed_count_in_bounds(&[]), 1);
+        assert_eq!(field.allowed_count_in_bounds(&[(3, 1), (0, 0)]), 0);
+    }
+
+    #[test]
+    fn test_caching_field_memoizes_and_invalidates() {
+        #[derive(Debug)]
+        struct Counting(Arc<std::sync::atomic::AtomicUsize>);
+        impl Constraint for Counting {
+            fn allows(&self, _coords: &SpaceCoordinates) -> bool {
+                self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                true
+            }
+            fn describe(&self) -> String {
+                "counting".to_string()
+            }
+        }
+
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut field = Field::new();
+        field.add_constraint(Counting(calls.clone()));
+        let mut cached = CachingField::new(field);
+        let c = SpaceCoordinates::new(vec![50]);
+
+        assert!(cached.allows(&c));
+        assert!(cached.allows(&c));
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(cached.cached_len(), 1);
+
+        cached.add_constraint(AtMost(10));
+        assert_eq!(cached.cached_len(), 0);
+        assert!(!cached.allows(&c));
+    }
+
+    #[test]
+    fn test_space_coordinate_arithmetic_pads_with_zeros() {
+        let a = SpaceCoordinates::new(vec![1, 2]);
+        let b = SpaceCoordinates::new(vec![10, 20, 30]);
+
+        assert_eq!(a.add(&b), SpaceCoordinates::new(vec![11, 22, 30]));
+        assert_eq!(b.add(&a), a.add(&b));
+        assert_eq!(a.scale(-3), SpaceCoordinates::new(vec![-3, -6]));
+        assert_eq!(a.manhattan_distance(&b), 9 + 18 + 30);
+
+        // Overflow saturates instead of panicking.
+        let max = SpaceCoordinates::new(vec![i64::MAX, i64::MIN]);
+        assert_eq!(
+            max.add(&a),
+            SpaceCoordinates::new(vec![i64::MAX, i64::MIN + 2])
+        );
+        assert_eq!(
+            max.scale(2),
+            SpaceCoordinates::new(vec![i64::MAX, i64::MIN])
+        );
+        assert_eq!(max.manhattan_distance(&max.scale(-1)), i64::MAX);
+    }
+
+    #[test]
+    fn test_with_axis_replaces_or_grows() {
+        let c = SpaceCoordinates::new(vec![1, 2]);
+        assert_eq!(c.with_axis(0, 9), SpaceCoordinates::new(vec![9, 2]));
+        assert_eq!(
+            c.with_axis(4, 7),
+            SpaceCoordinates::new(vec![1, 2, 0, 0, 7])
+        );
+        assert_eq!(c, SpaceCoordinates::new(vec![1, 2]));
+    }
+
+    #[test]
+    fn test_segment_try_new_checks_dimensionality() {
+        let segment = Segment::try_new(SpaceCoordinates::new(vec![1, 2]), 2).unwrap();
+        assert_eq!(segment, Segment::from_values(vec![1, 2]));
+
+        let err = Segment::try_new(SpaceCoordinates::new(vec![1, 2, 3]), 2).unwrap_err();
+        assert!(err.contains("expected 2"));
+    }
+
+    #[test]
+    fn test_field_to_dot() {
+        let mut field = Field::new();
+        field.add_transition(
+            SpaceCoordinates::new(vec![0]),
+            SpaceCoordinates::new(vec![1]),
+            0.5,
+        );
+        field.add_transition(
+            SpaceCoordinates::new(vec![1]),
+            SpaceCoordinates::new(vec![2]),
+            1.0,
+        );
+
+        let dot = field.to_dot();
+        assert!(dot.starts_with("digraph {"));
+        assert!(dot.ends_with('}'));
+        assert_eq!(dot.lines().filter(|l| l.contains("->")).count(), 2);
+        assert_eq!(dot.lines().filter(|l| l.contains("[label=\"[")).count(), 3);
+        assert!(dot.contains("[label=\"0.5\"]"));
+    }
+
+    #[test]
+    fn test_normalized_to_and_uniform_dim() {
+        let c = SpaceCoordinates::new(vec![1, 2, 3]);
+        assert_eq!(c.normalized_to(2), SpaceCoordinates::new(vec![1, 2]));
+        assert_eq!(
+            c.normalized_to(5),
+            SpaceCoordinates::new(vec![1, 2, 3, 0, 0])
+        );
+        assert_eq!(c.normalized_to(3), c);
+
+        let ragged = [c.clone(), SpaceCoordinates::new(vec![4])];
+        assert_eq!(SpaceCoordinates::is_uniform_dim(&ragged), None);
+        let uniform: Vec<_> = ragged.iter().map(|c| c.normalized_to(2)).collect();
+        assert_eq!(SpaceCoordinates::is_uniform_dim(&uniform), Some(2));
+        assert_eq!(SpaceCoordinates::is_uniform_dim(&[]), None);
+    }
+
+    #[test]
+    fn test_segment_identity_is_the_id() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::Hasher;
+
+        let hash_of = |value: &dyn Fn(&mut DefaultHasher)| {
+            let mut hasher = DefaultHasher::new();
+            value(&mut hasher);
+            hasher.finish()
+        };
+
+        let a = Segment::from_values(vec![3, 4]);
+        let b = Segment::new(SpaceCoordinates::new(vec![3, 4]));
+        assert_eq!(a, b);
+        assert!(a.same_coordinates(&b));
+        assert!(!a.same_coordinates(&Segment::from_values(vec![4, 3])));
+        assert_eq!(hash_of(&|h| a.hash(h)), hash_of(&|h| a.id().hash(h)));
+    }
+
+    #[test]
+    fn test_segment_id_hex_round_trip() {
+        let id = *Segment::from_values(vec![1, -2, 3]).id();
+        let text = id.to_string();
+        assert_eq!(text.len(), 64);
+        assert_eq!(SegmentId::from_hex(&text), Ok(id));
+        assert!(SegmentId::from_hex("abcd").is_err());
+        assert!(SegmentId::from_hex(&"zz".repeat(32)).is_err());
+    }
 }
@@ -1,8 +1,12 @@
+pub mod compiler_pipeline;
 pub mod core;
 pub mod scheme;
 pub mod ss_parser;
-use crate::core::{Constraint, Field, Projector, Segment, SpaceCoordinates};
+use crate::core::{Constraint, Field, Projector, Segment, SegmentId, SpaceCoordinates};
+use crate::scheme::ObservationRules;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
+use std::sync::Arc;
 
 // ==================== CONSTRAINT IMPLEMENTATIONS ====================
 
@@ -58,6 +62,97 @@ impl Constraint for EvenConstraint {
     }
 }
 
+/// Constraint for cyclic axes (e.g. angles in 0–359): the axis value is reduced
+/// modulo `period` and must lie on the inclusive arc `[lo, hi]`. When `lo > hi`
+/// the arc wraps through zero, so `[350, 10]` covers 350..=359 and 0..=10.
+#[derive(Debug, Clone)]
+pub struct CyclicRangeConstraint {
+    axis: usize,
+    period: i64,
+    lo: i64,
+    hi: i64,
+}
+
+impl CyclicRangeConstraint {
+    /// Panics if `period` is not positive; see `try_new`.
+    pub fn new(axis: usize, period: i64, lo: i64, hi: i64) -> Self {
+        Self::try_new(axis, period, lo, hi).expect("cyclic period must be positive")
+    }
+
+    /// Like `new`, but rejects a non‑positive `period` with an error.
+    pub fn try_new(axis: usize, period: i64, lo: i64, hi: i64) -> Result<Self, String> {
+        if period <= 0 {
+            return Err(format!("Cyclic period must be positive, got {}", period));
+        }
+        Ok(Self {
+            axis,
+            period,
+            lo: lo.rem_euclid(period),
+            hi: hi.rem_euclid(period),
+        })
+    }
+}
+
+impl Constraint for CyclicRangeConstraint {
+    fn allows(&self, coords: &SpaceCoordinates) -> bool {
+        coords
+            .get_axis(self.axis)
+            .map(|v| {
+                let v = v.rem_euclid(self.period);
+                if self.lo <= self.hi {
+                    v >= self.lo && v <= self.hi
+                } else {
+                    v >= self.lo || v <= self.hi
+                }
+            })
+            .unwrap_or(false)
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "axis[{}] mod {} ∈ arc [{}, {}]",
+            self.axis, self.period, self.lo, self.hi
+        )
+    }
+}
+
+/// Constraint backed by an arbitrary predicate.
+#[derive(Clone)]
+pub struct FnConstraint {
+    predicate: Arc<dyn Fn(&SpaceCoordinates) -> bool + Send + Sync>,
+    description: String,
+}
+
+impl FnConstraint {
+    pub fn new(
+        description: impl Into<String>,
+        predicate: impl Fn(&SpaceCoordinates) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            predicate: Arc::new(predicate),
+            description: description.into(),
+        }
+    }
+}
+
+impl Debug for FnConstraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnConstraint")
+            .field("description", &self.description)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Constraint for FnConstraint {
+    fn allows(&self, coords: &SpaceCoordinates) -> bool {
+        (self.predicate)(coords)
+    }
+
+    fn describe(&self) -> String {
+        self.description.clone()
+    }
+}
+
 // ==================== OBSERVATION FUNCTIONS ====================
 
 /// Observe a single point: project if the coordinate is allowed by the field.
@@ -69,6 +164,92 @@ pub fn observe<P: Projector>(field: &Field, segment: &Segment, projector: &P) ->
     }
 }
 
+/// Reasons an observation request can be refused.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AuthError {
+    #[error("Observer '{0}' is not authorized")]
+    NotAuthorized(String),
+}
+
+/// Like `observe`, but first checks `observer_id` against the allow‑list in
+/// `rules.context`. A `None` allow‑list admits every observer.
+pub fn observe_authorized<P: Projector>(
+    rules: &ObservationRules,
+    observer_id: &str,
+    field: &Field,
+    segment: &Segment,
+    projector: &P,
+) -> Result<Option<P::Output>, AuthError> {
+    if let Some(allowed) = &rules.context.allowed_observers
+        && !allowed.contains(observer_id)
+    {
+        return Err(AuthError::NotAuthorized(observer_id.to_string()));
+    }
+    Ok(observe(field, segment, projector))
+}
+
+/// Observe a batch of segments with one projector.
+/// Each segment's result (as `observe` would return it) is keyed by its id.
+pub fn observe_batch<P: Projector>(
+    field: &Field,
+    segments: &[&Segment],
+    projector: &P,
+) -> HashMap<SegmentId, Option<P::Output>> {
+    segments
+        .iter()
+        .map(|segment| (*segment.id(), observe(field, segment, projector)))
+        .collect()
+}
+
+/// Observe a batch of segments with one projector, preserving input order.
+/// Segments the field rejects (or the projector cannot project) yield `None`,
+/// so results correlate index‑by‑index with `segments`.
+pub fn observe_batch_ordered<P: Projector>(
+    field: &Field,
+    segments: &[Segment],
+    projector: &P,
+) -> Vec<Option<P::Output>> {
+    segments
+        .iter()
+        .map(|segment| observe(field, segment, projector))
+        .collect()
+}
+
+/// Parallel `observe_batch` (requires the `rayon` feature).
+#[cfg(feature = "rayon")]
+pub fn observe_batch_par<P: Projector>(
+    field: &Field,
+    segments: &[&Segment],
+    projector: &P,
+) -> HashMap<SegmentId, Option<P::Output>>
+where
+    P::Output: Send,
+{
+    use rayon::prelude::*;
+    segments
+        .par_iter()
+        .map(|segment| (*segment.id(), observe(field, segment, projector)))
+        .collect()
+}
+
+/// Parallel `observe_batch_ordered` (requires the `rayon` feature); the field
+/// and projector are shared by reference and the output order is unchanged.
+#[cfg(feature = "rayon")]
+pub fn observe_batch_ordered_par<P: Projector>(
+    field: &Field,
+    segments: &[Segment],
+    projector: &P,
+) -> Vec<Option<P::Output>>
+where
+    P::Output: Send,
+{
+    use rayon::prelude::*;
+    segments
+        .par_iter()
+        .map(|segment| observe(field, segment, projector))
+        .collect()
+}
+
 /// Compute all possible next coordinates from the current segment, taking into account
 /// both the projector's interpretation of adjacency and the field's transition matrix,
 /// filtered by field constraints.
@@ -84,6 +265,162 @@ pub fn possible_next_coordinates<P: Projector>(
     candidates
 }
 
+/// Explore breadth‑first from `start` up to `max_depth` transitions and observe
+/// every reachable state. Each distinct output is reported once, with the
+/// minimum depth at which it was observed; results are sorted by ascending depth
+/// (ties keep discovery order), so "closer" observations come first.
+pub fn observe_tree_ranked<P: Projector>(
+    field: &Field,
+    start: &Segment,
+    projector: &P,
+    max_depth: usize,
+) -> Vec<(P::Output, usize)> {
+    let mut ranked: Vec<(P::Output, usize)> = Vec::new();
+    let mut seen_outputs = HashSet::new();
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::new();
+
+    if field.allows(start.coordinates()) {
+        visited.insert(start.coordinates().clone());
+        queue.push_back((start.clone(), 0));
+    }
+
+    while let Some((segment, depth)) = queue.pop_front() {
+        if let Some(output) = observe(field, &segment, projector)
+            && seen_outputs.insert(output.clone())
+        {
+            ranked.push((output, depth));
+        }
+        if depth == max_depth {
+            continue;
+        }
+        for next in possible_next_coordinates(field, &segment, projector) {
+            if visited.insert(next.clone()) {
+                queue.push_back((Segment::new(next), depth + 1));
+            }
+        }
+    }
+    ranked
+}
+
+/// Generate the tree of allowed coordinates reachable from `start` (breadth‑first,
+/// at most `max_depth` transitions) while never holding more than `max_nodes`
+/// coordinates. Returns the visited set and whether the cap cut the traversal short,
+/// giving a predictable memory ceiling for densely connected or untrusted fields.
+pub fn generate_tree_bounded<P: Projector>(
+    field: &Field,
+    start: &SpaceCoordinates,
+    projector: &P,
+    max_depth: usize,
+    max_nodes: usize,
+) -> (HashSet<SpaceCoordinates>, bool) {
+    let mut visited = HashSet::new();
+    if !field.allows(start) {
+        return (visited, false);
+    }
+    if max_nodes == 0 {
+        return (visited, true);
+    }
+
+    let mut queue = VecDeque::new();
+    visited.insert(start.clone());
+    queue.push_back((Segment::new(start.clone()), 0));
+
+    while let Some((segment, depth)) = queue.pop_front() {
+        if depth == max_depth {
+            continue;
+        }
+        for next in possible_next_coordinates(field, &segment, projector) {
+            if visited.contains(&next) {
+                continue;
+            }
+            if visited.len() >= max_nodes {
+                return (visited, true);
+            }
+            visited.insert(next.clone());
+            queue.push_back((Segment::new(next), depth + 1));
+        }
+    }
+    (visited, false)
+}
+
+/// Depth‑first search from `start` (at most `max_depth` transitions deep) reporting
+/// every back edge as the cycle of coordinates it closes, in path order.
+/// A coordinate that transitions to itself is reported as a length‑1 cycle.
+/// Each cycle is reported once, even when several paths reach it.
+pub fn detect_cycles<P: Projector>(
+    field: &Field,
+    start: &SpaceCoordinates,
+    projector: &P,
+    max_depth: usize,
+) -> Vec<Vec<SpaceCoordinates>> {
+    struct Search<'a, P> {
+        field: &'a Field,
+        projector: &'a P,
+        max_depth: usize,
+        /// Shallowest path length at which each coordinate was fully expanded;
+        /// reaching it again by a shorter path leaves more depth budget, so it
+        /// is explored again.
+        expanded_at: HashMap<SpaceCoordinates, usize>,
+        /// Cycles rotated to start at their smallest coordinate, for deduplication.
+        reported: HashSet<Vec<SpaceCoordinates>>,
+        cycles: Vec<Vec<SpaceCoordinates>>,
+    }
+
+    impl<P: Projector> Search<'_, P> {
+        fn visit(&mut self, path: &mut Vec<SpaceCoordinates>) {
+            if path.len() > self.max_depth {
+                return;
+            }
+            let current = path.last().expect("path is never empty").clone();
+            let mut seen = HashSet::new();
+            for next in possible_next_coordinates(
+                self.field,
+                &Segment::new(current.clone()),
+                self.projector,
+            ) {
+                if !seen.insert(next.clone()) {
+                    continue;
+                }
+                if let Some(pos) = path.iter().position(|c| *c == next) {
+                    self.report(&path[pos..]);
+                } else if self
+                    .expanded_at
+                    .get(&next)
+                    .is_none_or(|&len| len > path.len() + 1)
+                {
+                    path.push(next);
+                    self.visit(path);
+                    path.pop();
+                }
+            }
+            let len = self.expanded_at.entry(current).or_insert(path.len());
+            *len = (*len).min(path.len());
+        }
+
+        fn report(&mut self, cycle: &[SpaceCoordinates]) {
+            let min = (0..cycle.len()).min_by_key(|&i| &cycle[i]).unwrap_or(0);
+            let canonical = [&cycle[min..], &cycle[..min]].concat();
+            if self.reported.insert(canonical) {
+                self.cycles.push(cycle.to_vec());
+            }
+        }
+    }
+
+    let mut search = Search {
+        field,
+        projector,
+        max_depth,
+        expanded_at: HashMap::new(),
+        reported: HashSet::new(),
+        cycles: Vec::new(),
+    };
+    if field.allows(start) {
+        search.visit(&mut vec![start.clone()]);
+    }
+    search.cycles
+}
+
 // ==================== MODULE STRUCTURE ====================
 
 pub mod spaces {
@@ -96,3 +433,70 @@ pub mod spaces {
     pub mod basic;
 }
 pub use spaces::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Projects axis 0, with no intrinsic adjacency.
+    #[derive(Debug)]
+    struct AxisProjector;
+
+    impl Projector for AxisProjector {
+        type Output = i64;
+
+        fn project(&self, _field: &Field, segment: &Segment) -> Option<i64> {
+            segment.coordinates().get_axis(0)
+        }
+    }
+
+    #[test]
+    fn test_cyclic_range_constraint_wraps() {
+        let arc = CyclicRangeConstraint::new(0, 360, 350, 10);
+        let at = |v: i64| SpaceCoordinates::new(vec![v]);
+
+        assert!(arc.allows(&at(355)));
+        assert!(arc.allows(&at(5)));
+        assert!(arc.allows(&at(365)));
+        assert!(arc.allows(&at(-5)));
+        assert!(!arc.allows(&at(180)));
+
+        let plain = CyclicRangeConstraint::new(0, 360, 90, 180);
+        assert!(plain.allows(&at(450)));
+        assert!(!plain.allows(&at(200)));
+
+        assert!(CyclicRangeConstraint::try_new(0, 0, 0, 10).is_err());
+        assert!(CyclicRangeConstraint::try_new(0, -5, 0, 10).is_err());
+    }
+
+    #[test]
+    fn test_observe_authorized_checks_allow_list() {
+        use crate::scheme::{
+            ObservationContext, ObservationPriority, ObservationTrigger, ResolutionStrategy,
+        };
+
+        let mut rules = ObservationRules {
+            resolution: ResolutionStrategy::EntropyMaximization,
+            triggers: vec![ObservationTrigger::OnDemand],
+            priority: ObservationPriority::Normal,
+            context: ObservationContext::default(),
+        };
+        let field = Field::new();
+        let segment = Segment::from_value(4);
+
+        assert_eq!(
+            observe_authorized(&rules, "anyone", &field, &segment, &AxisProjector),
+            Ok(Some(4))
+        );
+
+        rules.context.allowed_observers = Some(HashSet::from(["alice".to_string()]));
+        assert_eq!(
+            observe_authorized(&rules, "alice", &field, &segment, &AxisProjector),
+            Ok(Some(4))
+        );
+        assert_eq!(
+            observe_authorized(&rules, "mallory", &field, &segment, &AxisProjector),
+            Err(AuthError::NotAuthorized("mallory".to_string()))
+        );
+    }
+}
@@ -114,10 +114,7 @@ fn test_segment_concept() -> Result<(), String> {
 
     // 2. Cryptographic identity
     let id = segment.id();
-    println!(
-        "  2. Cryptographic identity (BLAKE3): {}",
-        hex::encode(id.as_bytes())
-    );
+    println!("  2. Cryptographic identity (BLAKE3): {}", id);
 
     // 3. Same coordinates → Same identity (deterministic)
     let segment2 = Segment::new(coords.clone());
@@ -257,7 +254,7 @@ fn test_projector_concept() -> Result<(), String> {
     }
 
     // Arithmetic projector
-    let arith_projector = ArithmeticProjector;
+    let arith_projector = ArithmeticProjector::default();
     let arith_result = arith_projector.project(&empty_field, &segment);
     println!("  4. ArithmeticProjector result: {:?}", arith_result);
 
@@ -319,7 +316,7 @@ fn test_observation_concept() -> Result<(), String> {
     println!("     - No state mutation during observation");
 
     // Test possible_next_coordinates function
-    let next_coords = possible_next_coordinates(&field, &segment, &ArithmeticProjector);
+    let next_coords = possible_next_coordinates(&field, &segment, &ArithmeticProjector::default());
     println!("  3. Possible next coordinates (filtered by field):");
     println!(
         "     - {:?}",
@@ -341,7 +338,7 @@ fn test_space_concept() -> Result<(), String> {
         "     - Created from coordinates: {:?}",
         basic_space.coordinates().raw
     );
-    println!("     - ID: {}", hex::encode(basic_space.id().as_bytes()));
+    println!("     - ID: {}", basic_space.id());
 
     // Test deref to Segment
     let segment_ref: &Segment = &basic_space;
@@ -364,7 +361,7 @@ fn test_space_concept() -> Result<(), String> {
         "     - Created from value: {:?}",
         int_space.coordinates().raw
     );
-    println!("     - ID: {}", hex::encode(int_space.id().as_bytes()));
+    println!("     - ID: {}", int_space.id());
 
     // Test conversions
     let _from_segment = IntegerSpace::from_segment(segment_ref.clone());
@@ -386,10 +383,7 @@ fn test_scheme_concept() -> Result<(), String> {
 
     let grid_scheme = Grid2DTemplate::new(5, 5, GridTopology::FourConnected).build();
     println!("     - Scheme description: {}", grid_scheme.describe());
-    println!(
-        "     - Scheme ID: {}",
-        hex::encode(grid_scheme.id().as_bytes())
-    );
+    println!("     - Scheme ID: {}", grid_scheme.id());
 
     println!("\n  2. Scheme properties:");
     println!("     - Dimensions: {}", grid_scheme.dimensionality());
@@ -423,7 +417,7 @@ fn test_scheme_concept() -> Result<(), String> {
 
     // Verify structural constraints
     let valid_coords = SpaceCoordinates::new(vec![0]);
-    if let Err(err) = int_scheme.validate_structure(&valid_coords) {
+    if let Err(err) = int_scheme.validate_structure(&valid_coords, None) {
         // This is OK - depends on implementation
         println!("     - Structural constraints checked: {}", err);
     }
@@ -545,10 +539,7 @@ fn test_composite_and_transformed_schemes() -> Result<(), String> {
     };
     let composite = CompositeScheme::new(components, composition_rules);
     println!("     - Composite scheme created: {}", composite.describe());
-    println!(
-        "     - Composite ID: {}",
-        hex::encode(composite.id().as_bytes())
-    );
+    println!("     - Composite ID: {}", composite.id());
 
     // Verify composite contains segments from both grids
     let test_coords = SpaceCoordinates::new(vec![0, 0]);
@@ -579,10 +570,7 @@ fn test_composite_and_transformed_schemes() -> Result<(), String> {
         "     - Transformed scheme created: {}",
         transformed.describe()
     );
-    println!(
-        "     - Transformed ID: {}",
-        hex::encode(transformed.id().as_bytes())
-    );
+    println!("     - Transformed ID: {}", transformed.id());
 
     // Verify transformed scheme delegates to base
     assert_eq!(transformed.dimensionality(), 2);
@@ -1,4 +1,8 @@
+use crate::scheme::Scheme;
 use ssccs_poc::core::{Field, Projector, Segment, SpaceCoordinates};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
 
 #[derive(Debug, Clone)]
 pub struct IntegerProjector {
@@ -19,11 +23,72 @@ impl Projector for IntegerProjector {
     }
 
     // No intrinsic adjacency for this projector.
+
+    fn describe(&self) -> String {
+        format!("integer value of axis {}", self.axis)
+    }
+}
+
+/// A single next‑coordinate operation for `ArithmeticProjector`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithOp {
+    Inc,
+    Dec,
+    Double,
+    Halve, // integer division
+    AddConst(i64),
+    MulConst(i64),
+}
+
+impl ArithOp {
+    /// The operation applied to `value`, or `None` on `i64` overflow.
+    pub fn apply(&self, value: i64) -> Option<i64> {
+        match self {
+            ArithOp::Inc => value.checked_add(1),
+            ArithOp::Dec => value.checked_sub(1),
+            ArithOp::Double => value.checked_mul(2),
+            ArithOp::Halve => value.checked_div(2),
+            ArithOp::AddConst(k) => value.checked_add(*k),
+            ArithOp::MulConst(k) => value.checked_mul(*k),
+        }
+    }
+
+    fn symbol(&self) -> String {
+        match self {
+            ArithOp::Inc => "+1".to_string(),
+            ArithOp::Dec => "-1".to_string(),
+            ArithOp::Double => "*2".to_string(),
+            ArithOp::Halve => "/2".to_string(),
+            ArithOp::AddConst(k) => format!("{:+}", k),
+            ArithOp::MulConst(k) => format!("*{}", k),
+        }
+    }
 }
 
 // A projector that performs arithmetic operations to generate neighbours.
 #[derive(Debug, Clone)]
-pub struct ArithmeticProjector;
+pub struct ArithmeticProjector {
+    ops: Vec<ArithOp>,
+}
+
+impl ArithmeticProjector {
+    /// Generate neighbours with exactly the given operations, in order.
+    pub fn with_ops(ops: Vec<ArithOp>) -> Self {
+        Self { ops }
+    }
+}
+
+impl Default for ArithmeticProjector {
+    /// The classic +1, -1, *2, /2 neighbourhood.
+    fn default() -> Self {
+        Self::with_ops(vec![
+            ArithOp::Inc,
+            ArithOp::Dec,
+            ArithOp::Double,
+            ArithOp::Halve,
+        ])
+    }
+}
 
 impl Projector for ArithmeticProjector {
     type Output = i64;
@@ -33,13 +98,60 @@ impl Projector for ArithmeticProjector {
     }
 
     fn possible_next_coordinates(&self, coords: &SpaceCoordinates) -> Vec<SpaceCoordinates> {
+        // Duplicates and self‑loops (e.g. 0 * 2 == 0 / 2 == 0) are not "next" states,
+        // and operations that overflow yield no neighbour.
         let current = coords.get_axis(0).unwrap_or(0);
-        vec![
-            SpaceCoordinates::new(vec![current + 1]),
-            SpaceCoordinates::new(vec![current - 1]),
-            SpaceCoordinates::new(vec![current * 2]),
-            SpaceCoordinates::new(vec![current / 2]), // integer division
-        ]
+        let mut next: Vec<SpaceCoordinates> = Vec::with_capacity(self.ops.len());
+        for value in self.ops.iter().filter_map(|op| op.apply(current)) {
+            let candidate = SpaceCoordinates::new(vec![value]);
+            if value != current && !next.contains(&candidate) {
+                next.push(candidate);
+            }
+        }
+        next
+    }
+
+    fn describe(&self) -> String {
+        let ops: Vec<String> = self.ops.iter().map(ArithOp::symbol).collect();
+        format!("integer value of axis 0, neighbours {}", ops.join(", "))
+    }
+}
+
+/// Arithmetic neighbours (+1, -1, *2, /2) restricted to the inclusive range `[min, max]`,
+/// so tree generation never proposes coordinates a range‑limited field would reject.
+#[derive(Debug, Clone)]
+pub struct BoundedArithmeticProjector {
+    pub min: i64,
+    pub max: i64,
+}
+
+impl BoundedArithmeticProjector {
+    pub fn new(min: i64, max: i64) -> Self {
+        Self { min, max }
+    }
+}
+
+impl Projector for BoundedArithmeticProjector {
+    type Output = i64;
+
+    fn project(&self, _field: &Field, segment: &Segment) -> Option<Self::Output> {
+        segment.coordinates().get_axis(0)
+    }
+
+    fn possible_next_coordinates(&self, coords: &SpaceCoordinates) -> Vec<SpaceCoordinates> {
+        let mut next = ArithmeticProjector::default().possible_next_coordinates(coords);
+        next.retain(|c| {
+            c.get_axis(0)
+                .is_some_and(|v| (self.min..=self.max).contains(&v))
+        });
+        next
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "integer value of axis 0, neighbours +1, -1, *2, /2 within [{}, {}]",
+            self.min, self.max
+        )
     }
 }
 
@@ -58,4 +170,704 @@ impl Projector for ParityProjector {
             Some("odd".into())
         }
     }
+
+    fn describe(&self) -> String {
+        "parity (even/odd) of axis 0".to_string()
+    }
+}
+
+// A projector that labels an axis value with its histogram bucket.
+#[derive(Debug, Clone)]
+pub struct HistogramProjector {
+    axis: usize,
+    bucket_width: i64,
+}
+
+impl HistogramProjector {
+    pub fn new(axis: usize, bucket_width: i64) -> Self {
+        Self { axis, bucket_width }
+    }
+}
+
+impl Projector for HistogramProjector {
+    type Output = String;
+
+    fn project(&self, _field: &Field, segment: &Segment) -> Option<Self::Output> {
+        if self.bucket_width <= 0 {
+            return None;
+        }
+        let value = segment.coordinates().get_axis(self.axis)?;
+        Some(format!("bucket_{}", value.div_euclid(self.bucket_width)))
+    }
+}
+
+// A projector that evaluates a polynomial sum(coeffs[i] * value^i) at an axis value.
+#[derive(Debug, Clone)]
+pub struct PolynomialProjector {
+    axis: usize,
+    coeffs: Vec<i64>,
+}
+
+impl PolynomialProjector {
+    pub fn new(axis: usize, coeffs: Vec<i64>) -> Self {
+        Self { axis, coeffs }
+    }
+}
+
+impl Projector for PolynomialProjector {
+    type Output = i64;
+
+    // Horner evaluation in i128; returns None if the result (or an intermediate) overflows.
+    fn project(&self, _field: &Field, segment: &Segment) -> Option<Self::Output> {
+        let x = segment.coordinates().get_axis(self.axis)? as i128;
+        let mut acc: i128 = 0;
+        for &c in self.coeffs.iter().rev() {
+            acc = acc.checked_mul(x)?.checked_add(c as i128)?;
+        }
+        i64::try_from(acc).ok()
+    }
+}
+
+// A projector backed by an explicit coordinate → value table.
+#[derive(Debug, Clone)]
+pub struct LookupProjector<O> {
+    table: HashMap<SpaceCoordinates, O>,
+}
+
+impl<O> LookupProjector<O> {
+    pub fn new() -> Self {
+        Self {
+            table: HashMap::new(),
+        }
+    }
+
+    pub fn insert(mut self, coords: SpaceCoordinates, value: O) -> Self {
+        self.table.insert(coords, value);
+        self
+    }
+}
+
+impl<O> Default for LookupProjector<O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<O> Projector for LookupProjector<O>
+where
+    O: Clone + Debug + Eq + Hash + Send + Sync,
+{
+    type Output = O;
+
+    fn project(&self, _field: &Field, segment: &Segment) -> Option<Self::Output> {
+        self.table.get(segment.coordinates()).cloned()
+    }
+}
+
+/// `f64` wrapper with bitwise `Eq`/`Hash`, so real values can be projector outputs.
+/// Equality compares bit patterns: `NaN == NaN`, but `0.0 != -0.0`.
+#[derive(Debug, Clone, Copy)]
+pub struct OrderedF64(pub f64);
+
+impl PartialEq for OrderedF64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for OrderedF64 {}
+
+impl std::hash::Hash for OrderedF64 {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+// A projector that interprets an axis as a fixed-point real (value / scale).
+#[derive(Debug, Clone)]
+pub struct FloatProjector {
+    axis: usize,
+    scale: f64,
+}
+
+impl FloatProjector {
+    pub fn new(axis: usize, scale: f64) -> Self {
+        Self { axis, scale }
+    }
+}
+
+impl Projector for FloatProjector {
+    type Output = OrderedF64;
+
+    fn project(&self, _field: &Field, segment: &Segment) -> Option<Self::Output> {
+        if self.scale == 0.0 {
+            return None;
+        }
+        let value = segment.coordinates().get_axis(self.axis)?;
+        Some(OrderedF64(value as f64 / self.scale))
+    }
+}
+
+/// Multi‑valued projector: the positive divisors of an axis value's magnitude.
+/// `project` yields the magnitude itself (its largest divisor); zero has no
+/// finite divisor set and is not projected.
+#[derive(Debug, Clone)]
+pub struct DivisorsProjector {
+    axis: usize,
+}
+
+impl DivisorsProjector {
+    pub fn new(axis: usize) -> Self {
+        Self { axis }
+    }
+}
+
+impl Projector for DivisorsProjector {
+    type Output = u64;
+
+    fn project(&self, _field: &Field, segment: &Segment) -> Option<Self::Output> {
+        let n = segment.coordinates().get_axis(self.axis)?.unsigned_abs();
+        (n != 0).then_some(n)
+    }
+
+    /// Divisors in ascending order.
+    fn project_all(&self, field: &Field, segment: &Segment) -> Vec<Self::Output> {
+        let Some(n) = self.project(field, segment) else {
+            return Vec::new();
+        };
+        let mut small = Vec::new();
+        let mut large = Vec::new();
+        let mut d = 1;
+        while d * d <= n {
+            if n.is_multiple_of(d) {
+                small.push(d);
+                if d != n / d {
+                    large.push(n / d);
+                }
+            }
+            d += 1;
+        }
+        small.extend(large.into_iter().rev());
+        small
+    }
+
+    fn describe(&self) -> String {
+        format!("divisors of axis {}", self.axis)
+    }
+}
+
+/// Observes two aspects at once: yields `(a, b)` only when both inner
+/// projections succeed. Adjacency is the union of both projectors' neighbours.
+#[derive(Debug, Clone)]
+pub struct CompositeProjector<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A: Projector, B: Projector> CompositeProjector<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+}
+
+impl<A: Projector, B: Projector> Projector for CompositeProjector<A, B> {
+    type Output = (A::Output, B::Output);
+
+    fn project(&self, field: &Field, segment: &Segment) -> Option<Self::Output> {
+        Some((
+            self.first.project(field, segment)?,
+            self.second.project(field, segment)?,
+        ))
+    }
+
+    fn possible_next_coordinates(&self, coords: &SpaceCoordinates) -> Vec<SpaceCoordinates> {
+        let mut next = self.first.possible_next_coordinates(coords);
+        for c in self.second.possible_next_coordinates(coords) {
+            if !next.contains(&c) {
+                next.push(c);
+            }
+        }
+        next
+    }
+
+    fn describe(&self) -> String {
+        format!("({}, {})", self.first.describe(), self.second.describe())
+    }
+}
+
+/// Tries projectors in order and yields the first successful projection,
+/// e.g. "interpret axis 0, else axis 1". Adjacency is the union of all
+/// projectors' neighbours.
+#[derive(Debug)]
+pub struct FirstSuccessProjector<O> {
+    projectors: Vec<Box<dyn Projector<Output = O>>>,
+}
+
+impl<O> FirstSuccessProjector<O> {
+    pub fn new() -> Self {
+        Self {
+            projectors: Vec::new(),
+        }
+    }
+
+    /// Append a fallback, tried after all previously added projectors.
+    pub fn then(mut self, projector: impl Projector<Output = O> + 'static) -> Self {
+        self.projectors.push(Box::new(projector));
+        self
+    }
+}
+
+impl<O> Default for FirstSuccessProjector<O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<O> Projector for FirstSuccessProjector<O>
+where
+    O: Clone + Debug + PartialEq + Eq + Hash + Send + Sync + 'static,
+{
+    type Output = O;
+
+    fn project(&self, field: &Field, segment: &Segment) -> Option<Self::Output> {
+        self.projectors
+            .iter()
+            .find_map(|projector| projector.project(field, segment))
+    }
+
+    fn possible_next_coordinates(&self, coords: &SpaceCoordinates) -> Vec<SpaceCoordinates> {
+        let mut next: Vec<SpaceCoordinates> = Vec::new();
+        for c in self
+            .projectors
+            .iter()
+            .flat_map(|projector| projector.possible_next_coordinates(coords))
+        {
+            if !next.contains(&c) {
+                next.push(c);
+            }
+        }
+        next
+    }
+
+    fn describe(&self) -> String {
+        let parts: Vec<String> = self.projectors.iter().map(|p| p.describe()).collect();
+        format!("first of [{}]", parts.join(", "))
+    }
+}
+
+/// Projects coordinates relative to a scheme's lower bounds, giving 0‑based
+/// local indices for sub‑region schemes.
+#[derive(Debug, Clone)]
+pub struct LocalCoordinateProjector {
+    origin: Vec<i64>,
+}
+
+impl LocalCoordinateProjector {
+    /// Use the scheme's per‑axis minimum as the local origin
+    /// (an empty scheme has the absolute origin).
+    pub fn for_scheme(scheme: &Scheme) -> Self {
+        let origin = scheme
+            .coordinate_bounds()
+            .map(|bounds| bounds.iter().map(|&(min, _)| min).collect())
+            .unwrap_or_default();
+        Self { origin }
+    }
+}
+
+impl Projector for LocalCoordinateProjector {
+    type Output = Vec<i64>;
+
+    /// `None` if an axis offset overflows `i64`.
+    fn project(&self, _field: &Field, segment: &Segment) -> Option<Self::Output> {
+        segment
+            .coordinates()
+            .raw
+            .iter()
+            .enumerate()
+            .map(|(axis, &v)| v.checked_sub(self.origin.get(axis).copied().unwrap_or(0)))
+            .collect()
+    }
+
+    fn describe(&self) -> String {
+        format!("coordinates relative to origin {:?}", self.origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheme::{Grid2DTemplate, GridTopology, SchemeBuilder};
+    use ssccs_poc::{
+        RangeConstraint, detect_cycles, generate_tree_bounded, observe, observe_batch,
+        observe_batch_ordered, observe_tree_ranked,
+    };
+
+    #[test]
+    fn test_observe_tree_ranked_prefers_shallow_values() {
+        let mut field = Field::new();
+        field.add_constraint(RangeConstraint::new(0, 0, 20));
+
+        let ranked = observe_tree_ranked(
+            &field,
+            &Segment::from_value(3),
+            &ArithmeticProjector::default(),
+            2,
+        );
+        let depth_of = |v: i64| ranked.iter().find(|(o, _)| *o == v).map(|(_, d)| *d);
+
+        assert_eq!(ranked[0], (3, 0));
+        assert_eq!(depth_of(4), Some(1));
+        assert_eq!(depth_of(6), Some(1));
+        // 3 → 6 → 12 takes two steps
+        assert_eq!(depth_of(12), Some(2));
+        assert_eq!(depth_of(13), None);
+        assert!(ranked.windows(2).all(|w| w[0].1 <= w[1].1));
+    }
+
+    #[test]
+    fn test_observe_batch_matches_single_observations() {
+        let grid = Grid2DTemplate::new(3, 3, GridTopology::FourConnected).build();
+        let mut field = Field::new();
+        field.add_constraint(RangeConstraint::new(0, 0, 1));
+        let projector = IntegerProjector::new(1);
+
+        let segments: Vec<&Segment> = grid.segments().collect();
+        let batch = observe_batch(&field, &segments, &projector);
+
+        assert_eq!(batch.len(), 9);
+        for segment in segments {
+            assert_eq!(batch[segment.id()], observe(&field, segment, &projector));
+        }
+        assert_eq!(batch.values().filter(|r| r.is_some()).count(), 6);
+    }
+
+    #[test]
+    fn test_generate_tree_bounded_truncates() {
+        let mut field = Field::new();
+        field.add_constraint(RangeConstraint::new(0, -1000, 1000));
+        let start = SpaceCoordinates::new(vec![1]);
+
+        let (tree, truncated) =
+            generate_tree_bounded(&field, &start, &ArithmeticProjector::default(), 50, 10);
+        assert!(truncated);
+        assert_eq!(tree.len(), 10);
+
+        let (small, truncated) =
+            generate_tree_bounded(&field, &start, &ArithmeticProjector::default(), 1, 10);
+        assert!(!truncated);
+        // 1 → {2, 0}
+        assert_eq!(small.len(), 3);
+    }
+
+    #[test]
+    fn test_detect_cycles_reports_back_edges() {
+        let c = |v: i64| SpaceCoordinates::new(vec![v]);
+        let mut field = Field::new();
+        field.add_transition(c(0), c(1), 1.0);
+        field.add_transition(c(1), c(2), 1.0);
+        field.add_transition(c(2), c(0), 1.0);
+        field.add_transition(c(2), c(3), 1.0);
+        field.add_transition(c(3), c(3), 1.0);
+
+        let cycles = detect_cycles(&field, &c(0), &IntegerProjector::new(0), 10);
+        assert_eq!(cycles.len(), 2);
+        assert!(cycles.contains(&vec![c(0), c(1), c(2)]));
+        assert!(cycles.contains(&vec![c(3)]));
+
+        // The three‑step cycle needs depth 3 to close.
+        let shallow = detect_cycles(&field, &c(0), &IntegerProjector::new(0), 2);
+        assert!(shallow.is_empty());
+    }
+
+    #[test]
+    fn test_detect_cycles_revisits_shallower_paths() {
+        // 0 → 1 → 2 reaches 2 past the depth limit first; the direct edge
+        // 0 → 2 must still find the two‑step cycle 0 → 2 → 0.
+        let c = |v: i64| SpaceCoordinates::new(vec![v]);
+        let mut field = Field::new();
+        field.add_transition(c(0), c(1), 1.0);
+        field.add_transition(c(0), c(2), 1.0);
+        field.add_transition(c(1), c(2), 1.0);
+        field.add_transition(c(2), c(0), 1.0);
+
+        let cycles = detect_cycles(&field, &c(0), &IntegerProjector::new(0), 2);
+        assert_eq!(cycles, vec![vec![c(0), c(2)]]);
+
+        let deep = detect_cycles(&field, &c(0), &IntegerProjector::new(0), 10);
+        assert_eq!(deep.len(), 2);
+        assert!(deep.contains(&vec![c(0), c(1), c(2)]));
+        assert!(deep.contains(&vec![c(0), c(2)]));
+    }
+
+    #[test]
+    fn test_float_projector_scales_fixed_point() {
+        let field = Field::new();
+        let millivolts = FloatProjector::new(0, 1000.0);
+        assert_eq!(
+            millivolts.project(&field, &Segment::from_value(1500)),
+            Some(OrderedF64(1.5))
+        );
+        assert_eq!(
+            FloatProjector::new(0, 0.0).project(&field, &Segment::from_value(1)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_histogram_projector_buckets() {
+        let field = Field::new();
+        let histogram = HistogramProjector::new(0, 5);
+        let buckets: std::collections::HashSet<String> = (0..10)
+            .filter_map(|v| histogram.project(&field, &Segment::from_value(v)))
+            .collect();
+        assert_eq!(buckets.len(), 2);
+        assert!(buckets.contains("bucket_0") && buckets.contains("bucket_1"));
+        assert_eq!(
+            histogram.project(&field, &Segment::from_value(-1)),
+            Some("bucket_-1".to_string())
+        );
+        assert_eq!(
+            HistogramProjector::new(0, 0).project(&field, &Segment::from_value(3)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_output_type_name() {
+        assert!(IntegerProjector::new(0).output_type_name().contains("i64"));
+        assert!(ParityProjector.output_type_name().contains("String"));
+    }
+
+    #[test]
+    fn test_polynomial_projector() {
+        let field = Field::new();
+        let square_plus_one = PolynomialProjector::new(0, vec![1, 0, 1]);
+        assert_eq!(
+            square_plus_one.project(&field, &Segment::from_value(3)),
+            Some(10)
+        );
+        assert_eq!(
+            square_plus_one.project(&field, &Segment::from_value(-3)),
+            Some(10)
+        );
+
+        let huge = PolynomialProjector::new(0, vec![0, 0, 0, i64::MAX]);
+        assert_eq!(huge.project(&field, &Segment::from_value(1_000_000)), None);
+    }
+
+    #[test]
+    fn test_lookup_projector() {
+        let field = Field::new();
+        let labels = LookupProjector::new()
+            .insert(SpaceCoordinates::new(vec![0, 0]), "origin")
+            .insert(SpaceCoordinates::new(vec![1, 0]), "east");
+
+        assert_eq!(
+            labels.project(&field, &Segment::from_values(vec![1, 0])),
+            Some("east")
+        );
+        assert_eq!(
+            labels.project(&field, &Segment::from_values(vec![5, 5])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_projector_describe() {
+        assert!(IntegerProjector::new(2).describe().contains("axis 2"));
+        assert!(ParityProjector.describe().contains("parity"));
+        // Projectors without an override fall back to their type name.
+        assert!(
+            HistogramProjector::new(0, 5)
+                .describe()
+                .contains("HistogramProjector")
+        );
+    }
+
+    #[test]
+    fn test_observe_batch_ordered_preserves_order() {
+        let grid = Grid2DTemplate::new(3, 3, GridTopology::FourConnected).build();
+        let mut segments: Vec<Segment> = grid.segments().cloned().collect();
+        segments.sort_by_key(|s| s.coordinates().clone());
+
+        let mut field = Field::new();
+        field.add_constraint(RangeConstraint::new(0, 1, 2));
+        let results = observe_batch_ordered(&field, &segments, &IntegerProjector::new(0));
+
+        assert_eq!(results.len(), segments.len());
+        // Sorted lexicographically, the first three cells have x = 0 and are rejected.
+        assert_eq!(&results[..3], &[None, None, None]);
+        assert_eq!(&results[3..6], &[Some(1), Some(1), Some(1)]);
+        assert_eq!(&results[6..], &[Some(2), Some(2), Some(2)]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_batches_match_serial_on_large_input() {
+        use ssccs_poc::{observe_batch_ordered_par, observe_batch_par};
+
+        let mut field = Field::new();
+        field.add_constraint(RangeConstraint::new(0, 0, 50_000));
+        let segments: Vec<Segment> = (0..100_000).map(Segment::from_value).collect();
+        let projector = IntegerProjector::new(0);
+
+        let serial = observe_batch_ordered(&field, &segments, &projector);
+        assert_eq!(
+            observe_batch_ordered_par(&field, &segments, &projector),
+            serial
+        );
+        assert_eq!(serial[50_000], Some(50_000));
+        assert_eq!(serial[50_001], None);
+
+        let refs: Vec<&Segment> = segments.iter().collect();
+        assert_eq!(
+            observe_batch_par(&field, &refs, &projector),
+            observe_batch(&field, &refs, &projector)
+        );
+    }
+
+    #[test]
+    fn test_local_coordinate_projector_on_sub_region() {
+        let region =
+            SchemeBuilder::new()
+                .add_segments((5..8).flat_map(|x| {
+                    (5..8).map(move |y| Segment::new(SpaceCoordinates::new(vec![x, y])))
+                }))
+                .build();
+        let projector = LocalCoordinateProjector::for_scheme(&region);
+        let field = Field::new();
+
+        let corner = Segment::new(SpaceCoordinates::new(vec![5, 5]));
+        assert_eq!(projector.project(&field, &corner), Some(vec![0, 0]));
+        let far = Segment::new(SpaceCoordinates::new(vec![7, 6]));
+        assert_eq!(projector.project(&field, &far), Some(vec![2, 1]));
+
+        // Offsets beyond i64 yield no projection instead of overflowing.
+        let extremes = SchemeBuilder::new()
+            .add_segments([Segment::from_value(i64::MIN), Segment::from_value(i64::MAX)])
+            .build();
+        let projector = LocalCoordinateProjector::for_scheme(&extremes);
+        assert_eq!(
+            projector.project(&field, &Segment::from_value(i64::MIN)),
+            Some(vec![0])
+        );
+        assert_eq!(
+            projector.project(&field, &Segment::from_value(i64::MAX)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_arithmetic_projector_with_only_inc() {
+        let projector = ArithmeticProjector::with_ops(vec![ArithOp::Inc]);
+        let next = projector.possible_next_coordinates(&SpaceCoordinates::new(vec![7]));
+        assert_eq!(next, vec![SpaceCoordinates::new(vec![8])]);
+        assert_eq!(
+            ArithmeticProjector::default().describe(),
+            "integer value of axis 0, neighbours +1, -1, *2, /2"
+        );
+    }
+
+    #[test]
+    fn test_arithmetic_projector_drops_overflowing_neighbours() {
+        let projector =
+            ArithmeticProjector::with_ops(vec![ArithOp::MulConst(1 << 40), ArithOp::Dec]);
+        let next = projector.possible_next_coordinates(&SpaceCoordinates::new(vec![1 << 30]));
+        assert_eq!(next, vec![SpaceCoordinates::new(vec![(1 << 30) - 1])]);
+
+        assert_eq!(ArithOp::Inc.apply(i64::MAX), None);
+        assert_eq!(ArithOp::AddConst(-5).apply(i64::MIN), None);
+        assert_eq!(ArithOp::Halve.apply(i64::MIN), Some(i64::MIN / 2));
+    }
+
+    #[test]
+    fn test_arithmetic_projector_drops_duplicates_and_self_loops() {
+        let projector = ArithmeticProjector::default();
+        let at_zero = projector.possible_next_coordinates(&SpaceCoordinates::new(vec![0]));
+        assert_eq!(
+            at_zero,
+            vec![
+                SpaceCoordinates::new(vec![1]),
+                SpaceCoordinates::new(vec![-1])
+            ]
+        );
+
+        let at_one = projector.possible_next_coordinates(&SpaceCoordinates::new(vec![1]));
+        assert_eq!(
+            at_one,
+            vec![
+                SpaceCoordinates::new(vec![2]),
+                SpaceCoordinates::new(vec![0])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bounded_arithmetic_projector_drops_out_of_range() {
+        let projector = BoundedArithmeticProjector::new(0, 10);
+        let at_max = projector.possible_next_coordinates(&SpaceCoordinates::new(vec![10]));
+        assert_eq!(
+            at_max,
+            vec![
+                SpaceCoordinates::new(vec![9]),
+                SpaceCoordinates::new(vec![5])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_divisors_projector_projects_all_divisors() {
+        let field = Field::new();
+        let projector = DivisorsProjector::new(0);
+        assert_eq!(
+            projector.project_all(&field, &Segment::from_value(12)),
+            vec![1, 2, 3, 4, 6, 12]
+        );
+        assert_eq!(
+            projector.project_all(&field, &Segment::from_value(-9)),
+            vec![1, 3, 9]
+        );
+        assert!(
+            projector
+                .project_all(&field, &Segment::from_value(0))
+                .is_empty()
+        );
+
+        // Single-valued projectors fall back to `project`.
+        assert_eq!(
+            IntegerProjector::new(0).project_all(&field, &Segment::from_value(12)),
+            vec![12]
+        );
+    }
+
+    #[test]
+    fn test_composite_projector_requires_both() {
+        let field = Field::new();
+        let both = CompositeProjector::new(IntegerProjector::new(0), ParityProjector);
+        assert_eq!(
+            both.project(&field, &Segment::from_value(7)),
+            Some((7, "odd".to_string()))
+        );
+
+        let missing_axis = CompositeProjector::new(IntegerProjector::new(1), ParityProjector);
+        assert_eq!(missing_axis.project(&field, &Segment::from_value(7)), None);
+        let missing_axis = CompositeProjector::new(ParityProjector, IntegerProjector::new(1));
+        assert_eq!(missing_axis.project(&field, &Segment::from_value(7)), None);
+    }
+
+    #[test]
+    fn test_first_success_projector_falls_back() {
+        let field = Field::new();
+        let chain = FirstSuccessProjector::new()
+            .then(IntegerProjector::new(2))
+            .then(IntegerProjector::new(1));
+
+        let segment = Segment::new(SpaceCoordinates::new(vec![10, 20]));
+        assert_eq!(chain.project(&field, &segment), Some(20));
+        assert_eq!(chain.project(&field, &Segment::from_value(10)), None);
+        assert_eq!(
+            FirstSuccessProjector::<i64>::new().project(&field, &segment),
+            None
+        );
+    }
 }
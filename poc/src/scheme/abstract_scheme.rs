@@ -2,16 +2,20 @@
 
 //! Scheme abstraction layer -defines structural relationships without physical memory implementation
 
-use crate::core::{Constraint, Segment, SegmentId, SpaceCoordinates};
+use crate::core::{
+    Constraint, Field, Metric, Segment, SegmentId, SpaceCoordinates, segment_id_from_coords,
+};
 
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::ops::Range;
 use std::sync::Arc;
 
 // Type aliases for complex closure types
 type PredicateFn = Arc<dyn Fn(&Segment, &Segment) -> bool + Send + Sync>;
 type MappingFn = Arc<dyn Fn(&SpaceCoordinates) -> Option<LogicalAddress> + Send + Sync>;
+type ResolverFn = Arc<dyn Fn(&[SpaceCoordinates]) -> Option<SpaceCoordinates> + Send + Sync>;
 
 // ==================== SCHEME IDENTITY ====================
 
@@ -27,6 +31,25 @@ impl SchemeId {
     pub fn to_hex(&self) -> String {
         hex::encode(self.0)
     }
+
+    /// Parse the 64‑character hex form produced by `to_hex`.
+    pub fn from_hex(s: &str) -> Result<SchemeId, String> {
+        if s.len() != 64 {
+            return Err(format!(
+                "SchemeId hex must be 64 characters, got {}",
+                s.len()
+            ));
+        }
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(s, &mut bytes).map_err(|e| format!("Invalid SchemeId hex: {}", e))?;
+        Ok(SchemeId(bytes))
+    }
+}
+
+impl std::fmt::Display for SchemeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_hex())
+    }
 }
 
 // ==================== DIMENSIONAL ABSTRACTION ====================
@@ -44,6 +67,26 @@ pub struct Axis {
     pub metadata: HashMap<String, String>,
 }
 
+impl Axis {
+    /// `(range_start, range_end)` from the metadata, as recorded by the templates.
+    /// `None` if either key is absent or not an integer.
+    pub fn range(&self) -> Option<(i64, i64)> {
+        Some((
+            self.metadata_i64("range_start")?,
+            self.metadata_i64("range_end")?,
+        ))
+    }
+
+    /// `step` from the metadata; `None` if absent or not an integer.
+    pub fn step(&self) -> Option<i64> {
+        self.metadata_i64("step")
+    }
+
+    fn metadata_i64(&self, key: &str) -> Option<i64> {
+        self.metadata.get(key)?.trim().parse().ok()
+    }
+}
+
 /// Axis types -define meaning without physical representation
 #[derive(Clone, Debug, PartialEq)]
 pub enum AxisType {
@@ -299,9 +342,9 @@ pub struct ObservationRules {
 }
 
 /// solution strategy
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone)]
 pub enum ResolutionStrategy {
-    /// Deterministic selection (fixed algorithm)
+    /// Deterministic selection (fixed algorithm, see `resolve_first_valid` for `FIRST_VALID`)
     Deterministic {
         algorithm: String,
         parameters: HashMap<String, String>,
@@ -324,6 +367,164 @@ pub enum ResolutionStrategy {
 
     /// External resolver (runtime decision)
     External { resolver_id: String },
+
+    /// In-process resolver invoked with the candidate slice
+    Custom(ResolverFn),
+}
+
+impl std::fmt::Debug for ResolutionStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResolutionStrategy::Deterministic {
+                algorithm,
+                parameters,
+            } => f
+                .debug_struct("Deterministic")
+                .field("algorithm", algorithm)
+                .field("parameters", parameters)
+                .finish(),
+            ResolutionStrategy::Probabilistic {
+                distribution,
+                temperature,
+            } => f
+                .debug_struct("Probabilistic")
+                .field("distribution", distribution)
+                .field("temperature", temperature)
+                .finish(),
+            ResolutionStrategy::EnergyMinimization {
+                energy_function,
+                optimization_method,
+            } => f
+                .debug_struct("EnergyMinimization")
+                .field("energy_function", energy_function)
+                .field("optimization_method", optimization_method)
+                .finish(),
+            ResolutionStrategy::EntropyMaximization => write!(f, "EntropyMaximization"),
+            ResolutionStrategy::External { resolver_id } => f
+                .debug_struct("External")
+                .field("resolver_id", resolver_id)
+                .finish(),
+            ResolutionStrategy::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+/// Custom resolvers compare equal only if they share the same closure.
+impl PartialEq for ResolutionStrategy {
+    fn eq(&self, other: &Self) -> bool {
+        use ResolutionStrategy::*;
+        match (self, other) {
+            (
+                Deterministic {
+                    algorithm: a1,
+                    parameters: p1,
+                },
+                Deterministic {
+                    algorithm: a2,
+                    parameters: p2,
+                },
+            ) => a1 == a2 && p1 == p2,
+            (
+                Probabilistic {
+                    distribution: d1,
+                    temperature: t1,
+                },
+                Probabilistic {
+                    distribution: d2,
+                    temperature: t2,
+                },
+            ) => d1 == d2 && t1 == t2,
+            (
+                EnergyMinimization {
+                    energy_function: e1,
+                    optimization_method: o1,
+                },
+                EnergyMinimization {
+                    energy_function: e2,
+                    optimization_method: o2,
+                },
+            ) => e1 == e2 && o1 == o2,
+            (EntropyMaximization, EntropyMaximization) => true,
+            (External { resolver_id: r1 }, External { resolver_id: r2 }) => r1 == r2,
+            (Custom(f1), Custom(f2)) => Arc::ptr_eq(f1, f2),
+            _ => false,
+        }
+    }
+}
+
+impl ObservationRules {
+    /// Replace the resolution strategy with an in-process resolver.
+    pub fn with_custom_resolution<F>(mut self, resolver: F) -> Self
+    where
+        F: Fn(&[SpaceCoordinates]) -> Option<SpaceCoordinates> + Send + Sync + 'static,
+    {
+        self.resolution = ResolutionStrategy::Custom(Arc::new(resolver));
+        self
+    }
+}
+
+/// Algorithm name of the canonical deterministic resolution.
+pub const FIRST_VALID: &str = "first-valid";
+
+/// Canonical `first-valid` resolution: candidates are ordered lexicographically
+/// by coordinate and the smallest one is selected.
+///
+/// Ordering first means the result never depends on the order in which
+/// candidates were produced (e.g. from a `HashSet`).
+pub fn resolve_first_valid(candidates: &[SpaceCoordinates]) -> Option<SpaceCoordinates> {
+    candidates.iter().min().cloned()
+}
+
+/// Resolve one configuration among `candidates` according to `rules.resolution`.
+///
+/// - `Deterministic` with `FIRST_VALID` uses `resolve_first_valid`.
+/// - `EnergyMinimization` picks the lowest `energy`; ties go to the smaller coordinate.
+/// - `EntropyMaximization` picks the candidate whose `distribution` has the highest
+///   Shannon entropy; ties go to the smaller coordinate.
+/// - `Custom` delegates to the stored resolver with the candidate slice.
+///
+/// Strategies without a local executor resolve to `None`.
+pub fn resolve_configuration(
+    rules: &ObservationRules,
+    candidates: &[SpaceCoordinates],
+    energy: &dyn Fn(&SpaceCoordinates) -> f64,
+    distribution: &dyn Fn(&SpaceCoordinates) -> Vec<f64>,
+) -> Option<SpaceCoordinates> {
+    match &rules.resolution {
+        ResolutionStrategy::Deterministic { algorithm, .. } if algorithm == FIRST_VALID => {
+            resolve_first_valid(candidates)
+        }
+        ResolutionStrategy::EnergyMinimization { .. } => candidates
+            .iter()
+            .map(|c| (energy(c), c))
+            .min_by(|(ea, ca), (eb, cb)| ea.total_cmp(eb).then_with(|| ca.cmp(cb)))
+            .map(|(_, c)| c.clone()),
+        ResolutionStrategy::EntropyMaximization => candidates
+            .iter()
+            .map(|c| (shannon_entropy(&distribution(c)), c))
+            .min_by(|(ha, ca), (hb, cb)| hb.total_cmp(ha).then_with(|| ca.cmp(cb)))
+            .map(|(_, c)| c.clone()),
+        ResolutionStrategy::Custom(resolver) => resolver(candidates),
+        _ => None,
+    }
+}
+
+/// Shannon entropy (in nats) of `weights` after normalizing them to sum to 1.
+/// Zero (or negative) weights contribute nothing, so degenerate inputs such as
+/// an all‑zero or single‑spike distribution yield 0 rather than NaN.
+pub fn shannon_entropy(weights: &[f64]) -> f64 {
+    let total: f64 = weights.iter().filter(|&&w| w > 0.0).sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+    weights
+        .iter()
+        .filter(|&&w| w > 0.0)
+        .map(|&w| {
+            let p = w / total;
+            -p * p.ln()
+        })
+        .sum()
 }
 
 /// observation trigger
@@ -337,6 +538,50 @@ pub enum ObservationTrigger {
     ExternalEvent { event_id: String }, // external event
 }
 
+/// Whether `rules` call for an observation at the given constraint satisfaction
+/// ratio (see `Field::satisfaction_ratio`): true if any trigger is `OnDemand`
+/// or a `Threshold` whose value is at most `ratio`.
+pub fn should_observe(rules: &ObservationRules, constraint_satisfaction_ratio: f64) -> bool {
+    rules.triggers.iter().any(|trigger| match trigger {
+        ObservationTrigger::OnDemand => true,
+        ObservationTrigger::Threshold { value } => *value <= constraint_satisfaction_ratio,
+        _ => false,
+    })
+}
+
+/// Deterministic driver for `Periodic` triggers: counts ticks instead of
+/// reading a clock, so simulation loops can schedule observations reproducibly.
+#[derive(Clone, Debug, Default)]
+pub struct ObservationScheduler {
+    ticks: u64,
+}
+
+impl ObservationScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ticks elapsed so far.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Advance one tick; true when the tick count is a multiple of the smallest
+    /// non‑zero `Periodic` interval in `rules` (never, if there is none).
+    pub fn tick(&mut self, rules: &ObservationRules) -> bool {
+        self.ticks += 1;
+        rules
+            .triggers
+            .iter()
+            .filter_map(|trigger| match trigger {
+                ObservationTrigger::Periodic { interval } if *interval > 0 => Some(*interval),
+                _ => None,
+            })
+            .min()
+            .is_some_and(|interval| self.ticks.is_multiple_of(interval))
+    }
+}
+
 /// Observation Priority
 #[derive(Clone, Debug, PartialEq)]
 pub enum ObservationPriority {
@@ -432,6 +677,173 @@ pub enum CurveType {
     CustomOrder, // custom
 }
 
+// Space-filling curve codecs. Each axis value occupies `bits` bits, so only
+// coordinates in `0..2^bits` are representable (negative values must be offset
+// by the caller first), and `dims * bits` must fit in a `usize`.
+
+fn curve_fits(coords: &[i64], bits: usize) -> bool {
+    coords.len() * bits <= usize::BITS as usize
+        && coords
+            .iter()
+            .all(|&v| v >= 0 && (bits >= 63 || (v as u64) < (1u64 << bits)))
+}
+
+/// Whether `addr` is a valid address for `dims` axes of `bits` bits each.
+fn curve_address_fits(addr: usize, dims: usize, bits: usize) -> bool {
+    match dims.checked_mul(bits) {
+        Some(total) if total < usize::BITS as usize => addr >> total == 0,
+        Some(total) => total == usize::BITS as usize,
+        None => false,
+    }
+}
+
+/// Converts decoded axis values back to coordinates, rejecting values that
+/// `curve_fits` would not accept (only possible with 64‑bit axes).
+fn curve_values(values: Vec<u64>) -> Option<Vec<i64>> {
+    values.into_iter().map(|v| i64::try_from(v).ok()).collect()
+}
+
+/// Interleave the axis values' bits, most significant first, axis 0 leading.
+fn interleave(values: &[u64], bits: usize) -> usize {
+    let mut addr = 0usize;
+    for bit in (0..bits).rev() {
+        for v in values {
+            addr = (addr << 1) | ((v >> bit) & 1) as usize;
+        }
+    }
+    addr
+}
+
+fn deinterleave(addr: usize, dims: usize, bits: usize) -> Vec<u64> {
+    let mut values = vec![0u64; dims];
+    let mut shift = dims * bits;
+    for bit in (0..bits).rev() {
+        for v in values.iter_mut() {
+            shift -= 1;
+            *v |= (((addr >> shift) & 1) as u64) << bit;
+        }
+    }
+    values
+}
+
+/// Z-order (Morton) address of `coords`, or `None` if a value is negative or
+/// does not fit in `bits` bits.
+pub fn z_order_encode(coords: &[i64], bits: usize) -> Option<usize> {
+    if !curve_fits(coords, bits) {
+        return None;
+    }
+    let values: Vec<u64> = coords.iter().map(|&v| v as u64).collect();
+    Some(interleave(&values, bits))
+}
+
+/// Inverse of `z_order_encode` for `dims` axes of `bits` bits each, or `None`
+/// if `dims * bits` exceeds a `usize` or `addr` has bits beyond that width.
+pub fn z_order_decode(addr: usize, dims: usize, bits: usize) -> Option<Vec<i64>> {
+    if !curve_address_fits(addr, dims, bits) {
+        return None;
+    }
+    curve_values(deinterleave(addr, dims, bits))
+}
+
+/// Hilbert curve address of `coords` (any dimensionality), using Skilling's
+/// transpose algorithm. Same representability rules as `z_order_encode`.
+pub fn hilbert_encode(coords: &[i64], bits: usize) -> Option<usize> {
+    if !curve_fits(coords, bits) {
+        return None;
+    }
+    let mut x: Vec<u64> = coords.iter().map(|&v| v as u64).collect();
+    if x.is_empty() || bits == 0 {
+        return Some(0);
+    }
+    let n = x.len();
+
+    // Inverse undo
+    let mut q = 1u64 << (bits - 1);
+    while q > 1 {
+        let p = q - 1;
+        for i in 0..n {
+            if x[i] & q != 0 {
+                x[0] ^= p;
+            } else {
+                let t = (x[0] ^ x[i]) & p;
+                x[0] ^= t;
+                x[i] ^= t;
+            }
+        }
+        q >>= 1;
+    }
+
+    // Gray encode
+    for i in 1..n {
+        x[i] ^= x[i - 1];
+    }
+    let mut t = 0;
+    let mut q = 1u64 << (bits - 1);
+    while q > 1 {
+        if x[n - 1] & q != 0 {
+            t ^= q - 1;
+        }
+        q >>= 1;
+    }
+    for v in x.iter_mut() {
+        *v ^= t;
+    }
+
+    Some(interleave(&x, bits))
+}
+
+/// Inverse of `hilbert_encode` for `dims` axes of `bits` bits each. Same
+/// range rules as `z_order_decode`.
+pub fn hilbert_decode(addr: usize, dims: usize, bits: usize) -> Option<Vec<i64>> {
+    if !curve_address_fits(addr, dims, bits) {
+        return None;
+    }
+    let mut x = deinterleave(addr, dims, bits);
+    if dims > 0 && bits > 0 {
+        let n = dims;
+
+        // Gray decode
+        let t = x[n - 1] >> 1;
+        for i in (1..n).rev() {
+            x[i] ^= x[i - 1];
+        }
+        x[0] ^= t;
+
+        // Undo excess work
+        for level in 1..bits {
+            let q = 1u64 << level;
+            let p = q - 1;
+            for i in (0..n).rev() {
+                if x[i] & q != 0 {
+                    x[0] ^= p;
+                } else {
+                    let t = (x[0] ^ x[i]) & p;
+                    x[0] ^= t;
+                    x[i] ^= t;
+                }
+            }
+        }
+    }
+    curve_values(x)
+}
+
+/// Which endpoint of a directed relation comes first in a topological order.
+#[derive(Clone, Copy)]
+enum Precedence {
+    SourceFirst,
+    TargetFirst,
+}
+
+/// The directed relation graph has a cycle through `node`.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Directed cycle through segment {node}")]
+pub struct CycleError {
+    pub node: SegmentId,
+    /// Every segment that could not be ordered (the cycles and everything
+    /// depending on them), sorted by SegmentId.
+    pub unresolved: Vec<SegmentId>,
+}
+
 // ==================== SCHEME CORE ====================
 
 /// Scheme -structural blueprint (immutable)
@@ -464,6 +876,26 @@ pub struct Scheme {
     metadata: HashMap<String, String>,
 }
 
+/// Relation among an arbitrary group of segments (e.g. a clique or contraction group)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HyperRelation {
+    pub members: Vec<SegmentId>,
+    pub kind: String,
+}
+
+impl HyperRelation {
+    pub fn new(members: Vec<SegmentId>, kind: impl Into<String>) -> Self {
+        Self {
+            members,
+            kind: kind.into(),
+        }
+    }
+
+    pub fn contains(&self, id: &SegmentId) -> bool {
+        self.members.contains(id)
+    }
+}
+
 /// relationship graph
 #[derive(Clone, Default)]
 pub struct RelationGraph {
@@ -472,6 +904,9 @@ pub struct RelationGraph {
 
     /// Segment ← Relationship List (reverse)
     incoming: HashMap<SegmentId, Vec<(SegmentId, StructuralRelation)>>,
+
+    /// Relations spanning more than two segments
+    hyperedges: Vec<HyperRelation>,
 }
 
 impl RelationGraph {
@@ -487,6 +922,15 @@ impl RelationGraph {
         self.incoming.entry(to).or_default().push((from, relation));
     }
 
+    pub fn add_hyperedge(&mut self, hyperedge: HyperRelation) {
+        self.hyperedges.push(hyperedge);
+    }
+
+    /// All recorded hyperedges, in insertion order.
+    pub fn hyperedges(&self) -> &[HyperRelation] {
+        &self.hyperedges
+    }
+
     pub fn get_outgoing(&self, from: &SegmentId) -> Vec<(SegmentId, StructuralRelation)> {
         self.outgoing.get(from).cloned().unwrap_or_default()
     }
@@ -495,6 +939,45 @@ impl RelationGraph {
         self.incoming.get(to).cloned().unwrap_or_default()
     }
 
+    /// Every segment reachable from `root` along `Hierarchy` edges
+    /// (recorded parent → child), excluding `root` itself.
+    pub fn descendants(&self, root: &SegmentId) -> HashSet<SegmentId> {
+        self.hierarchy_closure(root, &self.outgoing)
+    }
+
+    /// Every segment reaching `root` along `Hierarchy` edges, excluding `root` itself.
+    pub fn ancestors(&self, root: &SegmentId) -> HashSet<SegmentId> {
+        self.hierarchy_closure(root, &self.incoming)
+    }
+
+    fn hierarchy_closure(
+        &self,
+        root: &SegmentId,
+        edges: &HashMap<SegmentId, Vec<(SegmentId, StructuralRelation)>>,
+    ) -> HashSet<SegmentId> {
+        // The visited set also guards against cycles in malformed hierarchies.
+        let mut visited = HashSet::new();
+        let mut stack = vec![*root];
+        while let Some(current) = stack.pop() {
+            for (next, relation) in edges.get(&current).into_iter().flatten() {
+                if matches!(relation, StructuralRelation::Hierarchy { .. })
+                    && *next != *root
+                    && visited.insert(*next)
+                {
+                    stack.push(*next);
+                }
+            }
+        }
+        visited
+    }
+
+    /// All recorded relations as `(from, to, relation)` triples.
+    pub fn edges(&self) -> impl Iterator<Item = (&SegmentId, &SegmentId, &StructuralRelation)> {
+        self.outgoing
+            .iter()
+            .flat_map(|(from, v)| v.iter().map(move |(to, r)| (from, to, r)))
+    }
+
     pub fn get_relations_between(
         &self,
         from: &SegmentId,
@@ -515,6 +998,7 @@ impl std::fmt::Debug for RelationGraph {
         f.debug_struct("RelationGraph")
             .field("outgoing", &self.outgoing.keys().collect::<Vec<_>>())
             .field("incoming", &self.incoming.keys().collect::<Vec<_>>())
+            .field("hyperedges", &self.hyperedges.len())
             .finish()
     }
 }
@@ -549,6 +1033,16 @@ impl Scheme {
         &self.axes
     }
 
+    /// Position of the first axis named `name`.
+    pub fn axis_index(&self, name: &str) -> Option<usize> {
+        self.axes.iter().position(|axis| axis.name == name)
+    }
+
+    /// Value of `coords` on the axis named `name`.
+    pub fn get_axis_value(&self, coords: &SpaceCoordinates, name: &str) -> Option<i64> {
+        coords.get_axis(self.axis_index(name)?)
+    }
+
     pub fn dimensionality(&self) -> usize {
         self.axes.len()
     }
@@ -565,10 +1059,117 @@ impl Scheme {
         self.segments.values()
     }
 
+    /// Collapse this scheme through `field`: the segments whose coordinates
+    /// the field allows, sorted by SegmentId.
+    pub fn segments_allowed_by(&self, field: &Field) -> Vec<&Segment> {
+        let mut allowed: Vec<&Segment> = self
+            .segments()
+            .filter(|s| field.allows(s.coordinates()))
+            .collect();
+        allowed.sort_by_key(|s| s.id());
+        allowed
+    }
+
     pub fn segment_ids(&self) -> impl Iterator<Item = &SegmentId> {
         self.segments.keys()
     }
 
+    pub fn relations(&self) -> &RelationGraph {
+        &self.relations
+    }
+
+    /// Pairs recorded with a `Custom` relation named `name` whose predicate still
+    /// holds for the actual segments. Pairs referring to segments not in the
+    /// scheme are skipped. Sorted by `(from, to)`.
+    pub fn segments_related_by(&self, name: &str) -> Vec<(SegmentId, SegmentId)> {
+        let mut pairs: Vec<_> = self
+            .relations
+            .edges()
+            .filter_map(|(from, to, relation)| match relation {
+                StructuralRelation::Custom {
+                    name: relation_name,
+                    predicate,
+                } if relation_name == name => {
+                    let a = self.segments.get(from)?;
+                    let b = self.segments.get(to)?;
+                    predicate(a, b).then_some((*from, *to))
+                }
+                _ => None,
+            })
+            .collect();
+        pairs.sort();
+        pairs.dedup();
+        pairs
+    }
+
+    /// Group segments by the `equivalence_class` of the `Equivalence` relations
+    /// they take part in (both endpoints join the class; `Reflexive` relations
+    /// contribute the segment itself). Classes linked through a shared segment by
+    /// `Transitive` relations are merged and keyed by the smallest class id.
+    pub fn equivalence_classes(&self) -> HashMap<u64, HashSet<SegmentId>> {
+        let mut classes: HashMap<u64, HashSet<SegmentId>> = HashMap::new();
+        // Transitive class ids each segment belongs to
+        let mut transitive: HashMap<SegmentId, Vec<u64>> = HashMap::new();
+        let mut parent: HashMap<u64, u64> = HashMap::new();
+
+        for (from, to, relation) in self.relations.edges() {
+            let StructuralRelation::Equivalence {
+                equivalence_class,
+                symmetry,
+            } = relation
+            else {
+                continue;
+            };
+            let class = classes.entry(*equivalence_class).or_default();
+            class.insert(*from);
+            class.insert(*to);
+            parent
+                .entry(*equivalence_class)
+                .or_insert(*equivalence_class);
+            if *symmetry == SymmetryType::Transitive {
+                for id in [from, to] {
+                    transitive.entry(*id).or_default().push(*equivalence_class);
+                }
+            }
+        }
+
+        fn find(parent: &mut HashMap<u64, u64>, class: u64) -> u64 {
+            let p = parent[&class];
+            if p == class {
+                return class;
+            }
+            let root = find(parent, p);
+            parent.insert(class, root);
+            root
+        }
+
+        for ids in transitive.values() {
+            for pair in ids.windows(2) {
+                let (a, b) = (find(&mut parent, pair[0]), find(&mut parent, pair[1]));
+                if a != b {
+                    // Keep the smaller id as the representative.
+                    parent.insert(a.max(b), a.min(b));
+                }
+            }
+        }
+
+        let mut merged: HashMap<u64, HashSet<SegmentId>> = HashMap::new();
+        for (class, members) in classes {
+            let root = find(&mut parent, class);
+            merged.entry(root).or_default().extend(members);
+        }
+        merged
+    }
+
+    /// Hyperedges that include `id` as a member.
+    pub fn hyperedges_of(&self, id: &SegmentId) -> Vec<&HyperRelation> {
+        self.relations
+            .hyperedges
+            .iter()
+            .filter(|h| h.contains(id))
+            .collect()
+    }
+
     /// Structured relationship-based neighbor lookup
     pub fn structural_neighbors(
         &self,
@@ -590,24 +1191,425 @@ impl Scheme {
             .collect()
     }
 
-    /// Structural constraint verification
-    pub fn validate_structure(&self, coords: &SpaceCoordinates) -> Result<(), String> {
+    /// Plain adjacency list over all structural neighbors: every segment is a key
+    /// (isolated ones map to an empty list) and each neighbor list is sorted and
+    /// deduplicated.
+    pub fn to_adjacency_list(&self) -> HashMap<SegmentId, Vec<SegmentId>> {
+        self.segments
+            .keys()
+            .map(|id| {
+                let mut neighbors: Vec<SegmentId> = self
+                    .structural_neighbors(id, None)
+                    .into_iter()
+                    .map(|(neighbor, _)| neighbor)
+                    .collect();
+                neighbors.sort();
+                neighbors.dedup();
+                (*id, neighbors)
+            })
+            .collect()
+    }
+
+    /// Weakly connected components of the relation graph (edge direction ignored).
+    /// Every segment belongs to exactly one component, isolated ones to a
+    /// singleton; relations to segments outside the scheme are ignored.
+    /// Components are ordered by their smallest segment id.
+    pub fn connected_components(&self) -> Vec<HashSet<SegmentId>> {
+        let mut undirected: HashMap<SegmentId, Vec<SegmentId>> = HashMap::new();
+        for (from, to, _) in self.relations.edges() {
+            if self.segments.contains_key(from) && self.segments.contains_key(to) {
+                undirected.entry(*from).or_default().push(*to);
+                undirected.entry(*to).or_default().push(*from);
+            }
+        }
+
+        let mut ids: Vec<_> = self.segments.keys().copied().collect();
+        ids.sort();
+        let mut visited = HashSet::new();
+        let mut components = Vec::new();
+        for start in ids {
+            if !visited.insert(start) {
+                continue;
+            }
+            let mut component = HashSet::from([start]);
+            let mut stack = vec![start];
+            while let Some(current) = stack.pop() {
+                for next in undirected.get(&current).into_iter().flatten() {
+                    if visited.insert(*next) {
+                        component.insert(*next);
+                        stack.push(*next);
+                    }
+                }
+            }
+            components.push(component);
+        }
+        components
+    }
+
+    /// Whether the directed relations form a cycle: `Dependency` relations and
+    /// directed adjacency (`AdjacencyType::Graph`, or any adjacency whose
+    /// metadata has `edge_type == "directed"`). Other adjacency is symmetric
+    /// (grids store it in both directions) and is ignored.
+    pub fn has_directed_cycle(&self) -> bool {
+        self.topological_order().is_err()
+    }
+
+    /// Segments ordered along the directed relations (see `has_directed_cycle`):
+    /// a directed adjacency `a → b` puts `a` before `b`, and a segment comes
+    /// after the segments it depends on. Layers are taken in order and each is
+    /// sorted by SegmentId, so the order is deterministic.
+    pub fn topological_order(&self) -> Result<Vec<SegmentId>, CycleError> {
+        let layers = self.precedence_layers(|relation| match relation {
+            StructuralRelation::Dependency { .. } => Some(Precedence::TargetFirst),
+            StructuralRelation::Adjacency {
+                relation_type,
+                metadata,
+                ..
+            } if *relation_type == AdjacencyType::Graph
+                || metadata.get("edge_type").is_some_and(|t| t == "directed") =>
+            {
+                Some(Precedence::SourceFirst)
+            }
+            _ => None,
+        })?;
+        Ok(layers.into_iter().flatten().collect())
+    }
+
+    /// Groups segments into topological layers of the `Dependency` relations via
+    /// Kahn's algorithm (an edge `a → b` means `a` depends on `b`, so `b` lands
+    /// in an earlier layer). Segments without dependencies form layer 0; each
+    /// layer is sorted by SegmentId. Relations to segments outside the scheme
+    /// are ignored.
+    pub fn dependency_layers(&self) -> Result<Vec<Vec<SegmentId>>, CycleError> {
+        self.precedence_layers(|relation| {
+            matches!(relation, StructuralRelation::Dependency { .. })
+                .then_some(Precedence::TargetFirst)
+        })
+    }
+
+    /// Kahn's algorithm over the relations `precedence` orients; the rest are
+    /// ignored.
+    fn precedence_layers(
+        &self,
+        precedence: impl Fn(&StructuralRelation) -> Option<Precedence>,
+    ) -> Result<Vec<Vec<SegmentId>>, CycleError> {
+        let mut pending: HashMap<SegmentId, usize> =
+            self.segments.keys().map(|id| (*id, 0)).collect();
+        let mut predecessors: HashMap<SegmentId, Vec<SegmentId>> = HashMap::new();
+        let mut successors: HashMap<SegmentId, Vec<SegmentId>> = HashMap::new();
+        for (from, to, relation) in self.relations.edges() {
+            if !self.segments.contains_key(from) || !self.segments.contains_key(to) {
+                continue;
+            }
+            let (before, after) = match precedence(relation) {
+                Some(Precedence::SourceFirst) => (*from, *to),
+                Some(Precedence::TargetFirst) => (*to, *from),
+                None => continue,
+            };
+            *pending.get_mut(&after).unwrap() += 1;
+            predecessors.entry(after).or_default().push(before);
+            successors.entry(before).or_default().push(after);
+        }
+
+        let mut layers = Vec::new();
+        let mut ready: Vec<SegmentId> = pending
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(id, _)| *id)
+            .collect();
+        while !ready.is_empty() {
+            ready.sort();
+            let mut next = Vec::new();
+            for id in &ready {
+                pending.remove(id);
+                for successor in successors.get(id).into_iter().flatten() {
+                    if let Some(count) = pending.get_mut(successor) {
+                        *count -= 1;
+                        if *count == 0 {
+                            next.push(*successor);
+                        }
+                    }
+                }
+            }
+            layers.push(std::mem::replace(&mut ready, next));
+        }
+
+        if pending.is_empty() {
+            return Ok(layers);
+        }
+
+        // Every leftover segment still has a leftover predecessor, so walking
+        // predecessors must eventually revisit a segment, which lies on a cycle.
+        let mut unresolved: Vec<SegmentId> = pending.into_keys().collect();
+        unresolved.sort();
+        let leftover: HashSet<SegmentId> = unresolved.iter().copied().collect();
+        let mut current = unresolved[0];
+        let mut seen = HashSet::new();
+        while seen.insert(current) {
+            current = *predecessors[&current]
+                .iter()
+                .filter(|id| leftover.contains(id))
+                .min()
+                .unwrap();
+        }
+        Err(CycleError {
+            node: current,
+            unresolved,
+        })
+    }
+
+    /// Segment closest to `coords` under `metric`, ties broken by smallest id.
+    /// This is an O(n) linear scan over all segments.
+    pub fn nearest_segment(&self, coords: &SpaceCoordinates, metric: Metric) -> Option<&Segment> {
+        self.segments
+            .values()
+            .map(|segment| (segment.distance_to(coords, metric), segment))
+            .min_by(|(da, a), (db, b)| da.total_cmp(db).then_with(|| a.id().cmp(b.id())))
+            .map(|(_, segment)| segment)
+    }
+
+    /// Structural constraint verification, honoring each constraint's scope:
+    /// - `Global` always applies.
+    /// - `Local`/`Regional` apply only to a `segment_id` inside the scope.
+    /// - `Dimensional(axis)` applies only when `coords` has that axis.
+    /// - `Relational(rel)` applies only when `segment_id` takes part in a relation
+    ///   of the same kind.
+    ///
+    /// The error names the scope of the rejecting constraint.
+    pub fn validate_structure(
+        &self,
+        coords: &SpaceCoordinates,
+        segment_id: Option<&SegmentId>,
+    ) -> Result<(), String> {
         for constraint in &self.structural_constraints {
-            if !constraint.constraint.allows(coords) {
+            if self.constraint_applies(&constraint.scope, coords, segment_id)
+                && !constraint.constraint.allows(coords)
+            {
                 return Err(format!(
-                    "Structural constraint violation: {:?}",
-                    constraint.constraint_type
+                    "Structural constraint violation: {:?} (scope {:?})",
+                    constraint.constraint_type, constraint.scope
                 ));
             }
         }
         Ok(())
     }
 
+    fn constraint_applies(
+        &self,
+        scope: &ConstraintScope,
+        coords: &SpaceCoordinates,
+        segment_id: Option<&SegmentId>,
+    ) -> bool {
+        match scope {
+            ConstraintScope::Global => true,
+            ConstraintScope::Local(id) => segment_id == Some(id),
+            ConstraintScope::Regional(ids) => segment_id.is_some_and(|s| ids.contains(s)),
+            ConstraintScope::Dimensional(axis) => coords.get_axis(*axis).is_some(),
+            ConstraintScope::Relational(relation) => segment_id.is_some_and(|s| {
+                let kind = std::mem::discriminant(relation);
+                self.relations
+                    .get_outgoing(s)
+                    .iter()
+                    .chain(self.relations.get_incoming(s).iter())
+                    .any(|(_, r)| std::mem::discriminant(r) == kind)
+            }),
+        }
+    }
+
     /// Logical address mapping (not physical address)
     pub fn map_to_logical_address(&self, coords: &SpaceCoordinates) -> Option<LogicalAddress> {
         (self.memory_layout.mapping)(coords)
     }
 
+    /// Check every segment against the scheme's own invariants: dimensionality
+    /// matches the axis count, structural constraints hold, and the memory layout
+    /// maps it to an address. Failures are reported per segment, ordered by id.
+    pub fn validate_all_segments(&self) -> Result<(), Vec<(SegmentId, String)>> {
+        let mut ids: Vec<_> = self.segments.keys().collect();
+        ids.sort();
+
+        let mut failures = Vec::new();
+        for id in ids {
+            let coords = self.segments[id].coordinates();
+            if coords.dimensionality() != self.axes.len() {
+                failures.push((
+                    *id,
+                    format!(
+                        "{} dimensions, expected {} (axis count)",
+                        coords.dimensionality(),
+                        self.axes.len()
+                    ),
+                ));
+            }
+            if let Err(e) = self.validate_structure(coords, Some(id)) {
+                failures.push((*id, e));
+            }
+            if self.map_to_logical_address(coords).is_none() {
+                failures.push((*id, "No logical address mapping".to_string()));
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
+
+    /// Per‑axis `(min, max)` over all contained segments, or `None` for an empty scheme.
+    /// Segments of lower dimensionality only contribute to the axes they have.
+    pub fn coordinate_bounds(&self) -> Option<Vec<(i64, i64)>> {
+        let mut bounds: Option<Vec<(i64, i64)>> = None;
+        for segment in self.segments.values() {
+            let raw = &segment.coordinates().raw;
+            let b = bounds.get_or_insert_with(Vec::new);
+            for (axis, &v) in raw.iter().enumerate() {
+                match b.get_mut(axis) {
+                    Some((lo, hi)) => {
+                        *lo = (*lo).min(v);
+                        *hi = (*hi).max(v);
+                    }
+                    None => b.push((v, v)),
+                }
+            }
+        }
+        bounds
+    }
+
+    /// Predicate admitting coordinates whose logical address offset lies in `range`
+    /// (any address space). Coordinates the layout cannot map are rejected.
+    /// Wrap it in an `FnConstraint` to use it as a Field constraint.
+    pub fn address_constraint(
+        &self,
+        range: Range<u64>,
+    ) -> impl Fn(&SpaceCoordinates) -> bool + Send + Sync + 'static {
+        let mapping = Arc::clone(&self.memory_layout.mapping);
+        move |coords| mapping(coords).is_some_and(|addr| range.contains(&addr.offset))
+    }
+
+    /// Force‑directed 2D embedding (Fruchterman–Reingold) for visualising schemes
+    /// without natural planar coordinates. Relations are treated as undirected springs.
+    /// Deterministic for a fixed `seed`: nodes start at pseudo‑random points in
+    /// `[-1, 1]²` drawn from `seed` in SegmentId order.
+    pub fn force_layout(&self, iterations: usize, seed: u64) -> HashMap<SegmentId, (f64, f64)> {
+        let mut ids: Vec<SegmentId> = self.segments.keys().copied().collect();
+        ids.sort();
+        let n = ids.len();
+        if n == 0 {
+            return HashMap::new();
+        }
+        let index: HashMap<SegmentId, usize> =
+            ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        // SplitMix64, mapped to [-1, 1].
+        let mut state = seed;
+        let mut unit = || {
+            state = state.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^= z >> 31;
+            (z >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+        };
+        let mut pos: Vec<(f64, f64)> = (0..n).map(|_| (unit(), unit())).collect();
+        let edges: Vec<(usize, usize)> = self
+            .relations
+            .edges()
+            .filter_map(|(from, to, _)| Some((*index.get(from)?, *index.get(to)?)))
+            .filter(|(a, b)| a != b)
+            .collect();
+
+        let k = (4.0 / n as f64).sqrt();
+        for step in 0..iterations {
+            let temperature = 0.1 * (1.0 - step as f64 / iterations as f64);
+            let mut disp = vec![(0.0, 0.0); n];
+            for i in 0..n {
+                for j in (i + 1)..n {
+                    let (dx, dy) = (pos[i].0 - pos[j].0, pos[i].1 - pos[j].1);
+                    let dist = (dx * dx + dy * dy).sqrt().max(1e-9);
+                    let force = k * k / dist;
+                    disp[i].0 += dx / dist * force;
+                    disp[i].1 += dy / dist * force;
+                    disp[j].0 -= dx / dist * force;
+                    disp[j].1 -= dy / dist * force;
+                }
+            }
+            for &(a, b) in &edges {
+                let (dx, dy) = (pos[a].0 - pos[b].0, pos[a].1 - pos[b].1);
+                let dist = (dx * dx + dy * dy).sqrt().max(1e-9);
+                let force = dist * dist / k;
+                disp[a].0 -= dx / dist * force;
+                disp[a].1 -= dy / dist * force;
+                disp[b].0 += dx / dist * force;
+                disp[b].1 += dy / dist * force;
+            }
+            for (p, d) in pos.iter_mut().zip(&disp) {
+                let len = (d.0 * d.0 + d.1 * d.1).sqrt().max(1e-9);
+                let step_len = len.min(temperature);
+                p.0 += d.0 / len * step_len;
+                p.1 += d.1 / len * step_len;
+            }
+        }
+
+        ids.into_iter().zip(pos).collect()
+    }
+
+    /// GraphML rendering for graph tools (Gephi, Cytoscape): one node per
+    /// segment (hex id, with a `coords` attribute) and one directed edge per
+    /// adjacency relation (with a `relation_type` attribute such as `Manhattan(2)`).
+    pub fn to_graphml(&self) -> String {
+        fn escape(s: &str) -> String {
+            s.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;")
+        }
+
+        let mut ids: Vec<_> = self.segments.keys().collect();
+        ids.sort();
+        let mut edges: Vec<_> = self
+            .relations
+            .edges()
+            .filter_map(|(from, to, relation)| match relation {
+                StructuralRelation::Adjacency { relation_type, .. } => {
+                    Some((from, to, format!("{:?}", relation_type)))
+                }
+                _ => None,
+            })
+            .collect();
+        edges.sort();
+
+        let mut xml = String::new();
+        xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        xml.push_str(
+            "  <key id=\"coords\" for=\"node\" attr.name=\"coords\" attr.type=\"string\"/>\n",
+        );
+        xml.push_str(
+            "  <key id=\"relation_type\" for=\"edge\" attr.name=\"relation_type\" attr.type=\"string\"/>\n",
+        );
+        xml.push_str(&format!(
+            "  <graph id=\"{}\" edgedefault=\"directed\">\n",
+            self.id.to_hex()
+        ));
+        for id in ids {
+            xml.push_str(&format!(
+                "    <node id=\"{}\"><data key=\"coords\">{}</data></node>\n",
+                id,
+                escape(&format!("{:?}", self.segments[id].coordinates().raw))
+            ));
+        }
+        for (from, to, relation_type) in edges {
+            xml.push_str(&format!(
+                "    <edge source=\"{}\" target=\"{}\"><data key=\"relation_type\">{}</data></edge>\n",
+                from,
+                to,
+                escape(&relation_type)
+            ));
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+
     pub fn describe(&self) -> String {
         format!(
             "Scheme {}:\n  Dimensions: {}\n  Segments: {}\n  Relations: {}\n  Constraints: {}",
@@ -635,6 +1637,8 @@ pub struct SchemeBuilder {
     memory_layout: MemoryLayout,
     observation_rules: ObservationRules,
     metadata: HashMap<String, String>,
+    /// Validate segment dimensionality as segments are added
+    strict: bool,
 }
 
 impl Default for SchemeBuilder {
@@ -662,7 +1666,7 @@ impl Default for SchemeBuilder {
             },
             observation_rules: ObservationRules {
                 resolution: ResolutionStrategy::Deterministic {
-                    algorithm: "first-valid".to_string(),
+                    algorithm: FIRST_VALID.to_string(),
                     parameters: HashMap::new(),
                 },
                 triggers: vec![ObservationTrigger::OnDemand],
@@ -670,6 +1674,7 @@ impl Default for SchemeBuilder {
                 context: ObservationContext::default(),
             },
             metadata: HashMap::new(),
+            strict: false,
         }
     }
 }
@@ -696,7 +1701,7 @@ impl SchemeBuilder {
             },
             observation_rules: ObservationRules {
                 resolution: ResolutionStrategy::Deterministic {
-                    algorithm: "first-valid".to_string(),
+                    algorithm: FIRST_VALID.to_string(),
                     parameters: HashMap::new(),
                 },
                 triggers: vec![ObservationTrigger::OnDemand],
@@ -717,6 +1722,35 @@ impl SchemeBuilder {
         self
     }
 
+    /// Enable strict mode: `try_add_segment` rejects segments whose
+    /// dimensionality differs from the number of axes added so far.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Add a segment, failing fast in strict mode on a dimensionality mismatch.
+    /// Outside strict mode this behaves like `add_segment`.
+    pub fn try_add_segment(self, segment: Segment) -> Result<Self, String> {
+        let dims = segment.coordinates().dimensionality();
+        if self.strict && dims != self.axes.len() {
+            return Err(format!(
+                "Segment {} has {} dimensions, expected {} (axis count)",
+                segment.id(),
+                dims,
+                self.axes.len()
+            ));
+        }
+        Ok(self.add_segment(segment))
+    }
+
+    /// Add a segment, rejecting it unless its dimensionality matches the
+    /// number of axes added so far (regardless of strict mode).
+    pub fn add_segment_checked(self, segment: Segment) -> Result<Self, String> {
+        let segment = Segment::try_new(segment.coordinates().clone(), self.axes.len())?;
+        Ok(self.add_segment(segment))
+    }
+
     pub fn add_segments<I>(mut self, segments: I) -> Self
     where
         I: IntoIterator<Item = Segment>,
@@ -737,6 +1771,11 @@ impl SchemeBuilder {
         self
     }
 
+    pub fn add_hyperedge(mut self, hyperedge: HyperRelation) -> Self {
+        self.relations.add_hyperedge(hyperedge);
+        self
+    }
+
     pub fn add_structural_constraint(mut self, constraint: StructuralConstraint) -> Self {
         self.structural_constraints.push(constraint);
         self
@@ -784,15 +1823,65 @@ impl SchemeBuilder {
             }
         }
 
+        // Hyperedge hashing (member order is not significant)
+        let mut hyperedges: Vec<_> = self
+            .relations
+            .hyperedges
+            .iter()
+            .map(|h| {
+                let mut members = h.members.clone();
+                members.sort();
+                (h.kind.as_str(), members)
+            })
+            .collect();
+        hyperedges.sort();
+        for (kind, members) in hyperedges {
+            hasher.update(kind.as_bytes());
+            for member in members {
+                hasher.update(member.as_bytes());
+            }
+        }
+
         // Constraint hashing (type only)
         for constraint in &self.structural_constraints {
             hasher.update(format!("{:?}", constraint.constraint_type).as_bytes());
         }
+
+        // Layout hashing (type only; the mapping closure cannot be hashed)
+        hasher.update(format!("{:?}", self.memory_layout.layout_type).as_bytes());
+
+        // Resolution strategy hashing (discriminant only; `Custom` resolvers
+        // are closures and all hash to the same fixed tag)
+        let resolution_tag = match &self.observation_rules.resolution {
+            ResolutionStrategy::Deterministic { .. } => "Deterministic",
+            ResolutionStrategy::Probabilistic { .. } => "Probabilistic",
+            ResolutionStrategy::EnergyMinimization { .. } => "EnergyMinimization",
+            ResolutionStrategy::EntropyMaximization => "EntropyMaximization",
+            ResolutionStrategy::External { .. } => "External",
+            ResolutionStrategy::Custom(_) => "Custom",
+        };
+        hasher.update(resolution_tag.as_bytes());
     }
 
+    /// Build without validation. Duplicate or empty axis names are accepted,
+    /// which makes name‑based axis lookups ambiguous; prefer `build_checked`.
     pub fn build(self) -> Scheme {
         Scheme::new(self)
     }
+
+    /// Build, rejecting empty axis names and duplicate axis names.
+    pub fn build_checked(self) -> Result<Scheme, String> {
+        let mut seen = HashSet::new();
+        for (index, axis) in self.axes.iter().enumerate() {
+            if axis.name.is_empty() {
+                return Err(format!("Axis {} has an empty name", index));
+            }
+            if !seen.insert(axis.name.as_str()) {
+                return Err(format!("Duplicate axis name \"{}\"", axis.name));
+            }
+        }
+        Ok(self.build())
+    }
 }
 
 // ==================== PRE-DEFINED SCHEME TEMPLATES ====================
@@ -804,10 +1893,38 @@ pub mod grid2d {
     pub struct Grid2DTemplate {
         width: i64,
         height: i64,
-        #[allow(dead_code)]
         topology: GridTopology,
     }
 
+    /// Neighbor offsets for a grid topology at cell (x, y).
+    /// Triangular cells alternate between pointing up and down, so their
+    /// vertical neighbor depends on the cell parity.
+    fn neighbor_offsets(topology: &GridTopology, x: i64, y: i64) -> Vec<(i64, i64)> {
+        match topology {
+            GridTopology::FourConnected => vec![(1, 0), (-1, 0), (0, 1), (0, -1)],
+            GridTopology::EightConnected => vec![
+                (1, 0),
+                (-1, 0),
+                (0, 1),
+                (0, -1),
+                (1, 1),
+                (1, -1),
+                (-1, 1),
+                (-1, -1),
+            ],
+            // Axial coordinates
+            GridTopology::Hexagonal => vec![(1, 0), (-1, 0), (0, 1), (0, -1), (1, -1), (-1, 1)],
+            GridTopology::Triangular => {
+                if (x + y).rem_euclid(2) == 0 {
+                    vec![(1, 0), (-1, 0), (0, 1)]
+                } else {
+                    vec![(1, 0), (-1, 0), (0, -1)]
+                }
+            }
+            GridTopology::Custom(offsets) => offsets.clone(),
+        }
+    }
+
     impl Grid2DTemplate {
         pub fn new(width: i64, height: i64, topology: GridTopology) -> Self {
             Self {
@@ -817,7 +1934,16 @@ pub mod grid2d {
             }
         }
 
+        /// Build the grid using the topology given at construction.
         pub fn build(self) -> Scheme {
+            let topology = self.topology.clone();
+            self.build_with_topology(topology)
+        }
+
+        /// Build the grid, connecting cells according to `topology`.
+        /// Every in-bounds neighbor gets an outgoing `Adjacency` relation,
+        /// so each edge is recorded in both directions.
+        pub fn build_with_topology(self, topology: GridTopology) -> Scheme {
             let mut builder = SchemeBuilder::new()
                 .add_axis(Axis {
                     name: "x".to_string(),
@@ -851,7 +1977,31 @@ pub mod grid2d {
             }
 
             // Add adjacency relationship
-            // (simplification: actually creates relationships based on topology)
+            for x in 0..self.width {
+                for y in 0..self.height {
+                    let from = segment_id_from_coords(&SpaceCoordinates::new(vec![x, y]));
+                    for (dx, dy) in neighbor_offsets(&topology, x, y) {
+                        let (nx, ny) = (x + dx, y + dy);
+                        if (dx, dy) == (0, 0)
+                            || !(0..self.width).contains(&nx)
+                            || !(0..self.height).contains(&ny)
+                        {
+                            continue;
+                        }
+                        let to = segment_id_from_coords(&SpaceCoordinates::new(vec![nx, ny]));
+                        builder = builder.add_relation(
+                            from,
+                            to,
+                            StructuralRelation::Adjacency {
+                                relation_type: AdjacencyType::Grid(topology.clone()),
+                                weight: Some(1.0),
+                                metadata: HashMap::new(),
+                            },
+                        );
+                    }
+                }
+            }
+
             builder = builder.add_metadata("template".to_string(), "grid2d".to_string());
 
             builder.build()
@@ -967,3 +2117,796 @@ pub mod graph {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid_neighbor_count(scheme: &Scheme, x: i64, y: i64) -> usize {
+        let id = segment_id_from_coords(&SpaceCoordinates::new(vec![x, y]));
+        scheme.structural_neighbors(&id, None).len()
+    }
+
+    #[test]
+    fn test_grid_four_connected_adjacency() {
+        let scheme = grid2d::Grid2DTemplate::new(3, 3, GridTopology::FourConnected).build();
+        assert_eq!(grid_neighbor_count(&scheme, 0, 0), 2);
+        assert_eq!(grid_neighbor_count(&scheme, 1, 0), 3);
+        assert_eq!(grid_neighbor_count(&scheme, 1, 1), 4);
+    }
+
+    fn discrete_axis(name: &str) -> Axis {
+        Axis {
+            name: name.to_string(),
+            axis_type: AxisType::Discrete,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_strict_builder_rejects_dimensionality_mismatch() {
+        let builder = SchemeBuilder::new()
+            .strict()
+            .add_axis(discrete_axis("x"))
+            .add_axis(discrete_axis("y"));
+
+        let builder = builder
+            .try_add_segment(Segment::from_values(vec![1, 2]))
+            .unwrap();
+        let err = builder
+            .try_add_segment(Segment::from_values(vec![1, 2, 3]))
+            .err()
+            .unwrap();
+        assert!(err.contains("expected 2"));
+
+        // Non-strict builders accept any dimensionality.
+        assert!(
+            SchemeBuilder::new()
+                .add_axis(discrete_axis("x"))
+                .try_add_segment(Segment::from_values(vec![1, 2, 3]))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_add_segment_checked_validates_against_axes() {
+        let builder = SchemeBuilder::new()
+            .add_axis(discrete_axis("x"))
+            .add_axis(discrete_axis("y"))
+            .add_segment_checked(Segment::from_values(vec![0, 0]))
+            .unwrap();
+        let err = builder
+            .add_segment_checked(Segment::from_values(vec![0, 0, 1]))
+            .err()
+            .unwrap();
+        assert!(err.contains("3 dimensions, expected 2"));
+    }
+
+    #[test]
+    fn test_validate_all_segments_reports_out_of_range() {
+        #[derive(Debug)]
+        struct Below(i64);
+        impl Constraint for Below {
+            fn allows(&self, coords: &SpaceCoordinates) -> bool {
+                coords.raw.iter().all(|&v| v < self.0)
+            }
+            fn describe(&self) -> String {
+                format!("all axes < {}", self.0)
+            }
+        }
+
+        let builder = || {
+            SchemeBuilder::new()
+                .add_axis(discrete_axis("x"))
+                .add_segments((0..3).map(Segment::from_value))
+        };
+        assert!(builder().build().validate_all_segments().is_ok());
+
+        let outlier = Segment::from_value(99);
+        let scheme = builder()
+            .add_segment(outlier.clone())
+            .add_structural_constraint(StructuralConstraint {
+                constraint: Arc::new(Below(10)),
+                constraint_type: ConstraintType::Dimensional,
+                scope: ConstraintScope::Global,
+            })
+            .build();
+        let failures = scheme.validate_all_segments().unwrap_err();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, *outlier.id());
+        assert!(failures[0].1.contains("Structural constraint violation"));
+    }
+
+    #[test]
+    fn test_build_checked_rejects_bad_axis_names() {
+        let err = SchemeBuilder::new()
+            .add_axis(discrete_axis("x"))
+            .add_axis(discrete_axis("x"))
+            .build_checked()
+            .unwrap_err();
+        assert!(err.contains("\"x\""));
+
+        let err = SchemeBuilder::new()
+            .add_axis(discrete_axis("x"))
+            .add_axis(discrete_axis(""))
+            .build_checked()
+            .unwrap_err();
+        assert!(err.contains("Axis 1"));
+
+        assert!(
+            SchemeBuilder::new()
+                .add_axis(discrete_axis("x"))
+                .add_axis(discrete_axis("y"))
+                .build_checked()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_axis_lookup_by_name() {
+        let scheme = grid2d::Grid2DTemplate::new(3, 3, GridTopology::FourConnected).build();
+        let coords = SpaceCoordinates::new(vec![2, 1]);
+
+        assert_eq!(scheme.axis_index("x"), Some(0));
+        assert_eq!(scheme.axis_index("y"), Some(1));
+        assert_eq!(scheme.axis_index("z"), None);
+        assert_eq!(scheme.get_axis_value(&coords, "y"), Some(1));
+        assert_eq!(scheme.get_axis_value(&coords, "z"), None);
+    }
+
+    fn rules_with(resolution: ResolutionStrategy) -> ObservationRules {
+        ObservationRules {
+            resolution,
+            triggers: vec![ObservationTrigger::OnDemand],
+            priority: ObservationPriority::Normal,
+            context: ObservationContext::default(),
+        }
+    }
+
+    #[test]
+    fn test_resolve_configuration_minimizes_energy() {
+        let rules = rules_with(ResolutionStrategy::EnergyMinimization {
+            energy_function: "distance-to-4".to_string(),
+            optimization_method: "exhaustive".to_string(),
+        });
+        let candidates: Vec<_> = [7, 3, 5]
+            .iter()
+            .map(|&v| SpaceCoordinates::new(vec![v]))
+            .collect();
+        let energy = |c: &SpaceCoordinates| (c.raw[0] - 4).abs() as f64;
+        let no_distribution = |_: &SpaceCoordinates| Vec::new();
+
+        // 3 and 5 tie at energy 1; the smaller coordinate wins regardless of input order.
+        let chosen = resolve_configuration(&rules, &candidates, &energy, &no_distribution);
+        assert_eq!(chosen, Some(SpaceCoordinates::new(vec![3])));
+        let mut reversed = candidates.clone();
+        reversed.reverse();
+        assert_eq!(
+            resolve_configuration(&rules, &reversed, &energy, &no_distribution),
+            chosen
+        );
+
+        let first_valid = rules_with(ResolutionStrategy::Deterministic {
+            algorithm: FIRST_VALID.to_string(),
+            parameters: HashMap::new(),
+        });
+        assert_eq!(
+            resolve_configuration(&first_valid, &candidates, &energy, &no_distribution),
+            Some(SpaceCoordinates::new(vec![3]))
+        );
+        assert_eq!(
+            resolve_configuration(&rules, &[], &energy, &no_distribution),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resolve_configuration_maximizes_entropy() {
+        let rules = rules_with(ResolutionStrategy::EntropyMaximization);
+        let spiked = SpaceCoordinates::new(vec![0]);
+        let uniform = SpaceCoordinates::new(vec![1]);
+        let empty = SpaceCoordinates::new(vec![2]);
+        let distribution = |c: &SpaceCoordinates| match c.raw[0] {
+            0 => vec![1.0, 0.0, 0.0, 0.0],
+            1 => vec![0.25; 4],
+            _ => vec![0.0; 4],
+        };
+
+        let chosen = resolve_configuration(
+            &rules,
+            &[spiked.clone(), empty.clone(), uniform.clone()],
+            &|_| 0.0,
+            &distribution,
+        );
+        assert_eq!(chosen, Some(uniform));
+        assert_eq!(shannon_entropy(&distribution(&spiked)), 0.0);
+        assert_eq!(shannon_entropy(&distribution(&empty)), 0.0);
+        assert!((shannon_entropy(&[0.25; 4]) - 4f64.ln()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_resolve_configuration_uses_custom_resolver() {
+        let rules = rules_with(ResolutionStrategy::EntropyMaximization)
+            .with_custom_resolution(|candidates| candidates.last().cloned());
+        let candidates: Vec<_> = (0..3).map(|v| SpaceCoordinates::new(vec![v])).collect();
+
+        let chosen = resolve_configuration(&rules, &candidates, &|_| 0.0, &|_| Vec::new());
+        assert_eq!(chosen, Some(SpaceCoordinates::new(vec![2])));
+        assert_eq!(rules.resolution, rules.clone().resolution);
+        assert_eq!(format!("{:?}", rules.resolution), "Custom");
+    }
+
+    #[test]
+    fn test_should_observe_threshold_is_inclusive() {
+        let mut rules = rules_with(ResolutionStrategy::EntropyMaximization);
+        rules.triggers = vec![ObservationTrigger::Threshold { value: 0.5 }];
+
+        let mut field = crate::core::Field::new();
+        field.add_constraint(crate::RangeConstraint::new(0, 0, 4));
+        let ratio = field.satisfaction_ratio(&[(0, 9)]);
+        assert_eq!(ratio, 0.5);
+
+        assert!(should_observe(&rules, ratio));
+        assert!(!should_observe(&rules, 0.49));
+
+        rules.triggers.push(ObservationTrigger::OnDemand);
+        assert!(should_observe(&rules, 0.0));
+    }
+
+    #[test]
+    fn test_scheduler_fires_on_periodic_interval() {
+        let mut rules = rules_with(ResolutionStrategy::EntropyMaximization);
+        rules.triggers = vec![
+            ObservationTrigger::Periodic { interval: 5 },
+            ObservationTrigger::Periodic { interval: 3 },
+        ];
+
+        let mut scheduler = ObservationScheduler::new();
+        let fired: Vec<u64> = (0..10)
+            .filter_map(|_| scheduler.tick(&rules).then_some(scheduler.ticks()))
+            .collect();
+        assert_eq!(fired, vec![3, 6, 9]);
+
+        rules.triggers = vec![ObservationTrigger::OnDemand];
+        assert!(!scheduler.tick(&rules));
+    }
+
+    #[test]
+    fn test_validate_structure_respects_scope() {
+        #[derive(Debug)]
+        struct Never;
+        impl Constraint for Never {
+            fn allows(&self, _coords: &SpaceCoordinates) -> bool {
+                false
+            }
+            fn describe(&self) -> String {
+                "never".to_string()
+            }
+        }
+
+        let a = Segment::from_values(vec![0, 0]);
+        let b = Segment::from_values(vec![1, 0]);
+        let c = Segment::from_values(vec![2, 0]);
+        let scheme_with = |scope: ConstraintScope| {
+            SchemeBuilder::new()
+                .add_segments([a.clone(), b.clone(), c.clone()])
+                .add_relation(
+                    *a.id(),
+                    *b.id(),
+                    StructuralRelation::Hierarchy {
+                        parent: *a.id(),
+                        depth: 1,
+                        relation_type: HierarchyType::Containment,
+                    },
+                )
+                .add_structural_constraint(StructuralConstraint {
+                    constraint: Arc::new(Never),
+                    constraint_type: ConstraintType::Logical,
+                    scope,
+                })
+                .build()
+        };
+        let check = |scheme: &Scheme, seg: &Segment, with_id: bool| {
+            scheme
+                .validate_structure(seg.coordinates(), with_id.then_some(seg.id()))
+                .is_ok()
+        };
+
+        let global = scheme_with(ConstraintScope::Global);
+        assert!(!check(&global, &a, false));
+
+        let local = scheme_with(ConstraintScope::Local(*a.id()));
+        assert!(!check(&local, &a, true));
+        assert!(check(&local, &b, true));
+        assert!(check(&local, &a, false));
+
+        let regional = scheme_with(ConstraintScope::Regional(vec![*a.id(), *b.id()]));
+        assert!(!check(&regional, &b, true));
+        assert!(check(&regional, &c, true));
+
+        let dimensional = scheme_with(ConstraintScope::Dimensional(1));
+        assert!(!check(&dimensional, &a, false));
+        let one_d = SpaceCoordinates::new(vec![5]);
+        assert!(dimensional.validate_structure(&one_d, None).is_ok());
+        let err = dimensional
+            .validate_structure(a.coordinates(), None)
+            .unwrap_err();
+        assert!(err.contains("Dimensional(1)"));
+
+        let relational = scheme_with(ConstraintScope::Relational(StructuralRelation::Hierarchy {
+            parent: *c.id(),
+            depth: 0,
+            relation_type: HierarchyType::Containment,
+        }));
+        assert!(!check(&relational, &b, true));
+        assert!(check(&relational, &c, true));
+    }
+
+    #[test]
+    fn test_hierarchy_closure_over_containment_chain() {
+        let ids: Vec<SegmentId> = (0..4).map(|v| *Segment::from_value(v).id()).collect();
+        let contains = |parent: SegmentId| StructuralRelation::Hierarchy {
+            parent,
+            depth: 1,
+            relation_type: HierarchyType::Containment,
+        };
+
+        let mut graph = RelationGraph::new();
+        graph.add_relation(ids[0], ids[1], contains(ids[0]));
+        graph.add_relation(ids[1], ids[2], contains(ids[1]));
+        // A malformed back edge must not loop forever.
+        graph.add_relation(ids[2], ids[0], contains(ids[2]));
+        // Non-hierarchy edges are ignored.
+        graph.add_relation(
+            ids[2],
+            ids[3],
+            StructuralRelation::Adjacency {
+                relation_type: AdjacencyType::Manhattan(1),
+                weight: None,
+                metadata: HashMap::new(),
+            },
+        );
+
+        assert_eq!(graph.descendants(&ids[1]), HashSet::from([ids[2], ids[0]]));
+        assert_eq!(graph.ancestors(&ids[2]), HashSet::from([ids[1], ids[0]]));
+
+        let mut chain = RelationGraph::new();
+        chain.add_relation(ids[0], ids[1], contains(ids[0]));
+        chain.add_relation(ids[1], ids[2], contains(ids[1]));
+        assert_eq!(chain.descendants(&ids[0]), HashSet::from([ids[1], ids[2]]));
+        assert_eq!(chain.ancestors(&ids[2]), HashSet::from([ids[0], ids[1]]));
+        assert!(chain.ancestors(&ids[0]).is_empty());
+    }
+
+    #[test]
+    fn test_segments_related_by_custom_predicate() {
+        let same_parity = || StructuralRelation::Custom {
+            name: "same-parity".to_string(),
+            predicate: Arc::new(|a: &Segment, b: &Segment| {
+                a.coordinates().raw[0].rem_euclid(2) == b.coordinates().raw[0].rem_euclid(2)
+            }),
+        };
+        let segs: Vec<Segment> = (0..4).map(Segment::from_value).collect();
+        let missing = Segment::from_value(100);
+
+        let scheme = SchemeBuilder::new()
+            .add_segments(segs.clone())
+            .add_relation(*segs[0].id(), *segs[2].id(), same_parity())
+            .add_relation(*segs[0].id(), *segs[1].id(), same_parity())
+            .add_relation(*segs[1].id(), *segs[3].id(), same_parity())
+            .add_relation(*segs[1].id(), *missing.id(), same_parity())
+            .build();
+
+        let mut expected = vec![
+            (*segs[0].id(), *segs[2].id()),
+            (*segs[1].id(), *segs[3].id()),
+        ];
+        expected.sort();
+        assert_eq!(scheme.segments_related_by("same-parity"), expected);
+        assert!(scheme.segments_related_by("other").is_empty());
+    }
+
+    #[test]
+    fn test_transitive_equivalence_chain_collapses() {
+        let segs: Vec<Segment> = (0..6).map(Segment::from_value).collect();
+        let id = |i: usize| *segs[i].id();
+        let equiv = |class: u64, symmetry: SymmetryType| StructuralRelation::Equivalence {
+            equivalence_class: class,
+            symmetry,
+        };
+
+        let scheme = SchemeBuilder::new()
+            .add_segments(segs.clone())
+            .add_relation(id(0), id(1), equiv(3, SymmetryType::Transitive))
+            .add_relation(id(1), id(2), equiv(2, SymmetryType::Transitive))
+            .add_relation(id(2), id(3), equiv(1, SymmetryType::Transitive))
+            .add_relation(id(4), id(5), equiv(7, SymmetryType::Symmetric))
+            .add_relation(id(4), id(4), equiv(8, SymmetryType::Reflexive))
+            .build();
+
+        let classes = scheme.equivalence_classes();
+        assert_eq!(classes.len(), 3);
+        assert_eq!(classes[&1], HashSet::from([id(0), id(1), id(2), id(3)]));
+        assert_eq!(classes[&7], HashSet::from([id(4), id(5)]));
+        assert_eq!(classes[&8], HashSet::from([id(4)]));
+    }
+
+    #[test]
+    fn test_axis_metadata_range_and_step() {
+        let scheme = integer_line::IntegerLineTemplate::new(-5, 5, 2).build();
+        let axis = &scheme.axes()[0];
+        assert_eq!(axis.range(), Some((-5, 5)));
+        assert_eq!(axis.step(), Some(2));
+
+        let bare = discrete_axis("x");
+        assert_eq!(bare.range(), None);
+        assert_eq!(bare.step(), None);
+
+        let mut malformed = discrete_axis("x");
+        malformed
+            .metadata
+            .insert("range_start".to_string(), "0".to_string());
+        malformed
+            .metadata
+            .insert("range_end".to_string(), "ten".to_string());
+        malformed
+            .metadata
+            .insert("step".to_string(), "1.5".to_string());
+        assert_eq!(malformed.range(), None);
+        assert_eq!(malformed.step(), None);
+    }
+
+    #[test]
+    fn test_space_filling_curves_round_trip() {
+        for (dims, bits) in [(2usize, 3usize), (3, 2)] {
+            let side = 1i64 << bits;
+            let mut seen_z = HashSet::new();
+            let mut seen_h = HashSet::new();
+            for addr in 0..(1usize << (dims * bits)) {
+                let c = z_order_decode(addr, dims, bits).unwrap();
+                assert!(c.iter().all(|&v| (0..side).contains(&v)));
+                assert_eq!(z_order_encode(&c, bits), Some(addr));
+                seen_z.insert(c);
+
+                let h = hilbert_decode(addr, dims, bits).unwrap();
+                assert_eq!(hilbert_encode(&h, bits), Some(addr));
+                seen_h.insert(h);
+            }
+            assert_eq!(seen_z.len(), 1 << (dims * bits));
+            assert_eq!(seen_h.len(), 1 << (dims * bits));
+        }
+
+        // Consecutive Hilbert addresses are unit steps apart.
+        for addr in 1..64 {
+            let a = SpaceCoordinates::new(hilbert_decode(addr - 1, 2, 3).unwrap());
+            let b = SpaceCoordinates::new(hilbert_decode(addr, 2, 3).unwrap());
+            assert_eq!(a.manhattan_distance(&b), 1);
+        }
+
+        assert_eq!(z_order_encode(&[1, 0], 1), Some(0b10));
+        assert_eq!(z_order_encode(&[-1, 0], 4), None);
+        assert_eq!(hilbert_encode(&[16, 0], 4), None);
+
+        // Out-of-range widths and addresses are rejected rather than panicking.
+        assert_eq!(z_order_decode(0, 3, 30), None);
+        assert_eq!(hilbert_decode(0, 3, 30), None);
+        assert_eq!(z_order_decode(64, 2, 3), None);
+        assert_eq!(hilbert_decode(64, 2, 3), None);
+        assert_eq!(z_order_decode(usize::MAX, 1, 64), None);
+        assert_eq!(z_order_decode(0, 0, 5), Some(vec![]));
+    }
+
+    #[test]
+    fn test_to_graphml_counts_and_structure() {
+        let a = Segment::from_value(0);
+        let b = Segment::from_value(2);
+        let scheme = SchemeBuilder::new()
+            .add_segments([a.clone(), b.clone(), Segment::from_value(5)])
+            .add_relation(
+                *a.id(),
+                *b.id(),
+                StructuralRelation::Adjacency {
+                    relation_type: AdjacencyType::Manhattan(2),
+                    weight: None,
+                    metadata: HashMap::new(),
+                },
+            )
+            .build();
+
+        let xml = scheme.to_graphml();
+        assert!(xml.starts_with("<?xml"));
+        assert_eq!(xml.matches("<node ").count(), 3);
+        assert_eq!(xml.matches("</node>").count(), 3);
+        assert_eq!(xml.matches("<edge ").count(), 1);
+        assert_eq!(xml.matches("</edge>").count(), 1);
+        assert_eq!(xml.matches("<graph ").count(), 1);
+        assert!(xml.trim_end().ends_with("</graphml>"));
+        assert!(xml.contains("<data key=\"relation_type\">Manhattan(2)</data>"));
+        assert!(xml.contains(&format!("<node id=\"{}\">", a.id())));
+    }
+
+    #[test]
+    fn test_adjacency_list_covers_every_segment() {
+        let scheme = grid2d::Grid2DTemplate::new(3, 3, GridTopology::FourConnected).build();
+        let list = scheme.to_adjacency_list();
+        let id = |x, y| segment_id_from_coords(&SpaceCoordinates::new(vec![x, y]));
+
+        assert_eq!(list.len(), 9);
+        let mut corner = vec![id(1, 0), id(0, 1)];
+        corner.sort();
+        assert_eq!(list[&id(0, 0)], corner);
+        assert_eq!(list[&id(2, 2)].len(), 2);
+        assert_eq!(list[&id(1, 1)].len(), 4);
+        assert!(list.values().all(|n| n.windows(2).all(|w| w[0] < w[1])));
+
+        let lonely = Segment::from_value(7);
+        let isolated = SchemeBuilder::new().add_segment(lonely.clone()).build();
+        assert_eq!(isolated.to_adjacency_list()[lonely.id()], Vec::new());
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let grid = grid2d::Grid2DTemplate::new(3, 3, GridTopology::FourConnected).build();
+        assert_eq!(grid.connected_components().len(), 1);
+
+        // Two 2x2 sub-grids side by side with no relation between them.
+        let left = grid2d::Grid2DTemplate::new(2, 2, GridTopology::FourConnected).build();
+        let mut builder = SchemeBuilder::new().add_segments(left.segments().cloned());
+        for (from, to, relation) in left.relations().edges() {
+            builder = builder.add_relation(*from, *to, relation.clone());
+        }
+        let shift = |s: &Segment| {
+            let raw = &s.coordinates().raw;
+            Segment::from_values(vec![raw[0] + 10, raw[1]])
+        };
+        builder = builder.add_segments(left.segments().map(shift));
+        for (from, to, relation) in left.relations().edges() {
+            let from = shift(left.get_segment(from).unwrap());
+            let to = shift(left.get_segment(to).unwrap());
+            builder = builder.add_relation(*from.id(), *to.id(), relation.clone());
+        }
+        let split = builder
+            .add_segment(Segment::from_values(vec![50, 50]))
+            .build();
+
+        let components = split.connected_components();
+        let mut sizes: Vec<usize> = components.iter().map(|c| c.len()).collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![1, 4, 4]);
+    }
+
+    #[test]
+    fn test_topological_order_and_cycles() {
+        let segs: Vec<Segment> = (0..5).map(Segment::from_value).collect();
+        let id = |i: usize| *segs[i].id();
+        let depends = || StructuralRelation::Dependency {
+            dependent: id(0),
+            dependency_type: DependencyType::DataFlow,
+            strength: 1.0,
+        };
+
+        let dag = SchemeBuilder::new()
+            .add_segments(segs.clone())
+            .add_relation(id(0), id(1), depends())
+            .add_relation(id(0), id(2), depends())
+            .add_relation(id(1), id(3), depends())
+            .add_relation(id(2), id(3), depends())
+            .build();
+        let order = dag.topological_order().unwrap();
+        assert_eq!(order.len(), 5);
+        let pos = |i: usize| order.iter().position(|x| *x == id(i)).unwrap();
+        assert!(pos(1) < pos(0) && pos(2) < pos(0));
+        assert!(pos(3) < pos(1) && pos(3) < pos(2));
+        assert!(!dag.has_directed_cycle());
+
+        // 0 -> 1 -> 2 -> 1, with 4 hanging downstream of the cycle.
+        let cyclic = SchemeBuilder::new()
+            .add_segments(segs.clone())
+            .add_relation(id(0), id(1), depends())
+            .add_relation(id(1), id(2), depends())
+            .add_relation(id(2), id(1), depends())
+            .add_relation(id(2), id(4), depends())
+            .build();
+        let err = cyclic.topological_order().unwrap_err();
+        assert!(err.node == id(1) || err.node == id(2));
+        let mut unresolved = vec![id(0), id(1), id(2)];
+        unresolved.sort();
+        assert_eq!(err.unresolved, unresolved);
+        assert!(cyclic.has_directed_cycle());
+    }
+
+    #[test]
+    fn test_segments_allowed_by_field() {
+        use crate::RangeConstraint;
+
+        let grid = grid2d::Grid2DTemplate::new(4, 4, GridTopology::FourConnected).build();
+        let mut field = Field::new();
+        field.add_constraint(RangeConstraint::new(0, 1, 2));
+        field.add_constraint(RangeConstraint::new(1, 0, 1));
+
+        let mut cells: Vec<Vec<i64>> = grid
+            .segments_allowed_by(&field)
+            .iter()
+            .map(|s| s.coordinates().raw.clone())
+            .collect();
+        cells.sort();
+        assert_eq!(cells, vec![vec![1, 0], vec![1, 1], vec![2, 0], vec![2, 1]]);
+    }
+
+    #[test]
+    fn test_directed_graph_adjacency_cycles() {
+        let nodes = vec![vec![0], vec![1], vec![2]];
+        let chain =
+            graph::GraphTemplate::new(nodes.clone(), vec![(0, 1, 1.0), (1, 2, 1.0)]).build();
+        let order = chain.topological_order().unwrap();
+        let expected: Vec<SegmentId> = (0..3).map(|v| *Segment::from_value(v).id()).collect();
+        assert_eq!(order, expected);
+        assert!(!chain.has_directed_cycle());
+
+        let ring =
+            graph::GraphTemplate::new(nodes, vec![(0, 1, 1.0), (1, 2, 1.0), (2, 0, 1.0)]).build();
+        assert!(ring.has_directed_cycle());
+        assert_eq!(ring.topological_order().unwrap_err().unresolved.len(), 3);
+        // Graph adjacency is not a dependency, so the compiler's layering is unaffected.
+        assert_eq!(ring.dependency_layers().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_grid_adjacency_is_not_a_cycle() {
+        // Grid adjacency is stored in both directions but is not a dependency.
+        let grid = grid2d::Grid2DTemplate::new(2, 1, GridTopology::FourConnected).build();
+        assert!(!grid.has_directed_cycle());
+        assert_eq!(grid.topological_order().unwrap().len(), 2);
+        assert_eq!(grid.dependency_layers().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_nearest_segment_on_line() {
+        let line = integer_line::IntegerLineTemplate::new(0, 10, 2).build();
+        let at = |v: i64| SpaceCoordinates::new(vec![v]);
+
+        let hit = line.nearest_segment(&at(4), Metric::Manhattan).unwrap();
+        assert_eq!(hit.coordinates(), &at(4));
+        assert_eq!(hit.distance_to(&at(4), Metric::Euclidean), 0.0);
+
+        // 5 is equidistant from 4 and 6; the smaller id wins deterministically.
+        let tie = line.nearest_segment(&at(5), Metric::Euclidean).unwrap();
+        let (four, six) = (Segment::from_value(4), Segment::from_value(6));
+        assert_eq!(tie.id(), four.id().min(six.id()));
+
+        assert_eq!(
+            Metric::Chebyshev.distance(&SpaceCoordinates::new(vec![0, 0]), &at(3)),
+            3.0
+        );
+        assert!(
+            SchemeBuilder::new()
+                .build()
+                .nearest_segment(&at(0), Metric::Manhattan)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_scheme_id_hex_round_trip() {
+        let scheme = grid2d::Grid2DTemplate::new(2, 2, GridTopology::FourConnected).build();
+        let id = *scheme.id();
+        assert_eq!(SchemeId::from_hex(&id.to_hex()), Ok(id));
+        assert_eq!(id.to_string(), id.to_hex());
+        assert!(SchemeId::from_hex(&id.to_hex()[..62]).is_err());
+    }
+
+    #[test]
+    fn test_scheme_id_covers_layout_and_resolution() {
+        let builder = || {
+            SchemeBuilder::new()
+                .add_axis(discrete_axis("x"))
+                .add_segments((0..4).map(|x| Segment::from_values(vec![x])))
+        };
+        let linear = builder().build();
+        assert_eq!(*builder().build().id(), *linear.id());
+
+        let row_major = builder()
+            .set_memory_layout(MemoryLayout {
+                layout_type: LayoutType::RowMajor,
+                mapping: linear.memory_layout.mapping.clone(),
+                metadata: HashMap::new(),
+            })
+            .build();
+        assert_ne!(row_major.id(), linear.id());
+
+        let entropy = builder()
+            .set_observation_rules(rules_with(ResolutionStrategy::EntropyMaximization))
+            .build();
+        assert_ne!(entropy.id(), linear.id());
+    }
+
+    #[test]
+    fn test_force_layout_is_deterministic() {
+        let scheme = graph::GraphTemplate::new(
+            vec![vec![0], vec![1], vec![2], vec![3]],
+            vec![(0, 1, 1.0), (1, 2, 1.0), (2, 3, 1.0)],
+        )
+        .build();
+
+        let layout = scheme.force_layout(50, 7);
+        assert_eq!(layout, scheme.force_layout(50, 7));
+        assert_ne!(layout, scheme.force_layout(50, 8));
+        assert_eq!(layout.len(), 4);
+
+        let a = layout[Segment::from_value(0).id()];
+        let b = layout[Segment::from_value(1).id()];
+        let dist = ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt();
+        assert!(dist > 0.1, "connected nodes collapsed: {dist}");
+        assert!(layout.values().all(|(x, y)| x.is_finite() && y.is_finite()));
+    }
+
+    #[test]
+    fn test_address_constraint_limits_window() {
+        use crate::FnConstraint;
+        use crate::core::Field;
+
+        let scheme = integer_line::IntegerLineTemplate::new(0, 9, 1).build();
+        let mut field = Field::new();
+        field.add_constraint(FnConstraint::new(
+            "first half of address space",
+            scheme.address_constraint(0..5),
+        ));
+
+        let allowed: Vec<i64> = (0..10)
+            .filter(|&v| field.allows(&SpaceCoordinates::new(vec![v])))
+            .collect();
+        assert_eq!(allowed, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_coordinate_bounds() {
+        let grid = grid2d::Grid2DTemplate::new(4, 3, GridTopology::FourConnected).build();
+        assert_eq!(grid.coordinate_bounds(), Some(vec![(0, 3), (0, 2)]));
+
+        let line = integer_line::IntegerLineTemplate::new(-5, 5, 5).build();
+        assert_eq!(line.coordinate_bounds(), Some(vec![(-5, 5)]));
+
+        assert_eq!(SchemeBuilder::new().build().coordinate_bounds(), None);
+    }
+
+    #[test]
+    fn test_first_valid_is_order_independent() {
+        let a = SpaceCoordinates::new(vec![2, 1]);
+        let b = SpaceCoordinates::new(vec![1, 5]);
+        let c = SpaceCoordinates::new(vec![1, 3]);
+
+        let forward = resolve_first_valid(&[a.clone(), b.clone(), c.clone()]);
+        let backward = resolve_first_valid(&[c.clone(), b, a]);
+        assert_eq!(forward, Some(c));
+        assert_eq!(forward, backward);
+        assert_eq!(resolve_first_valid(&[]), None);
+    }
+
+    #[test]
+    fn test_grid_eight_connected_adjacency() {
+        let scheme = grid2d::Grid2DTemplate::new(3, 3, GridTopology::FourConnected)
+            .build_with_topology(GridTopology::EightConnected);
+        assert_eq!(grid_neighbor_count(&scheme, 2, 2), 3);
+        assert_eq!(grid_neighbor_count(&scheme, 1, 1), 8);
+    }
+
+    #[test]
+    fn test_hyperedge_visible_from_every_member() {
+        let segments: Vec<Segment> = (0..4)
+            .map(|i| Segment::new(SpaceCoordinates::new(vec![i])))
+            .collect();
+        let members: Vec<SegmentId> = segments[..3].iter().map(|s| *s.id()).collect();
+        let scheme = SchemeBuilder::new()
+            .add_segments(segments.clone())
+            .add_hyperedge(HyperRelation::new(members.clone(), "clique"))
+            .build();
+
+        for id in &members {
+            let hyperedges = scheme.hyperedges_of(id);
+            assert_eq!(hyperedges.len(), 1);
+            assert_eq!(hyperedges[0].kind, "clique");
+            assert_eq!(hyperedges[0].members, members);
+        }
+        assert!(scheme.hyperedges_of(segments[3].id()).is_empty());
+    }
+}
@@ -0,0 +1,246 @@
+// Qs core/poc/src/scheme/index.rs
+
+//! Spatial index over a scheme's segment coordinates (k-d tree)
+
+use super::abstract_scheme::Scheme;
+use crate::core::{Metric, SegmentId, SpaceCoordinates};
+
+/// k-d tree node; children index into `SchemeIndex::nodes`
+#[derive(Clone, Debug)]
+struct Node {
+    id: SegmentId,
+    coords: SpaceCoordinates,
+    axis: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+impl Node {
+    /// Signed offset of `coords` from this node along its split axis. Taken in
+    /// `f64` like `Metric::distance`, so extreme coordinates cannot overflow.
+    fn gap_to(&self, coords: &SpaceCoordinates) -> f64 {
+        coords.get_axis(self.axis).unwrap_or(0) as f64
+            - self.coords.get_axis(self.axis).unwrap_or(0) as f64
+    }
+}
+
+/// Immutable k-d tree over the segments of a `Scheme`, splitting on axes
+/// cyclically. Answers nearest-neighbour and radius queries without scanning
+/// every segment. Results match `Scheme::nearest_segment` (ties go to the
+/// smallest `SegmentId`).
+#[derive(Clone, Debug)]
+pub struct SchemeIndex {
+    nodes: Vec<Node>,
+    root: Option<usize>,
+    dims: usize,
+}
+
+impl SchemeIndex {
+    pub fn build(scheme: &Scheme) -> Self {
+        let mut points: Vec<(SegmentId, SpaceCoordinates)> = scheme
+            .segments()
+            .map(|s| (*s.id(), s.coordinates().clone()))
+            .collect();
+        let dims = points
+            .iter()
+            .map(|(_, c)| c.dimensionality())
+            .max()
+            .unwrap_or(0);
+
+        let mut index = Self {
+            nodes: Vec::with_capacity(points.len()),
+            root: None,
+            dims,
+        };
+        index.root = index.build_subtree(&mut points, 0);
+        index
+    }
+
+    fn build_subtree(
+        &mut self,
+        points: &mut [(SegmentId, SpaceCoordinates)],
+        depth: usize,
+    ) -> Option<usize> {
+        if points.is_empty() {
+            return None;
+        }
+        let axis = if self.dims == 0 { 0 } else { depth % self.dims };
+        // Ties on the split axis are ordered by id, keeping the tree shape deterministic.
+        points.sort_by_key(|(id, c)| (c.get_axis(axis).unwrap_or(0), *id));
+        let mid = points.len() / 2;
+        let (id, coords) = points[mid].clone();
+
+        let slot = self.nodes.len();
+        self.nodes.push(Node {
+            id,
+            coords,
+            axis,
+            left: None,
+            right: None,
+        });
+        let (lower, rest) = points.split_at_mut(mid);
+        let left = self.build_subtree(lower, depth + 1);
+        let right = self.build_subtree(&mut rest[1..], depth + 1);
+        self.nodes[slot].left = left;
+        self.nodes[slot].right = right;
+        Some(slot)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Segment closest to `coords` under `metric`, ties broken by smallest id.
+    pub fn nearest(&self, coords: &SpaceCoordinates, metric: Metric) -> Option<SegmentId> {
+        let mut best: Option<(f64, SegmentId)> = None;
+        self.nearest_in(self.root, coords, metric, &mut best);
+        best.map(|(_, id)| id)
+    }
+
+    fn nearest_in(
+        &self,
+        node: Option<usize>,
+        coords: &SpaceCoordinates,
+        metric: Metric,
+        best: &mut Option<(f64, SegmentId)>,
+    ) {
+        let Some(index) = node else {
+            return;
+        };
+        let node = &self.nodes[index];
+        let d = metric.distance(&node.coords, coords);
+        let better = match best {
+            Some((bd, bid)) => d < *bd || (d == *bd && node.id < *bid),
+            None => true,
+        };
+        if better {
+            *best = Some((d, node.id));
+        }
+
+        // The per-axis gap bounds the distance from below for every metric.
+        let gap = node.gap_to(coords);
+        let (near, far) = if gap < 0.0 {
+            (node.left, node.right)
+        } else {
+            (node.right, node.left)
+        };
+        self.nearest_in(near, coords, metric, best);
+        if best.is_none_or(|(bd, _)| gap.abs() <= bd) {
+            self.nearest_in(far, coords, metric, best);
+        }
+    }
+
+    /// Segments within Euclidean distance `r` of `coords` (inclusive), sorted by id.
+    pub fn within_radius(&self, coords: &SpaceCoordinates, r: f64) -> Vec<SegmentId> {
+        let mut found = Vec::new();
+        let mut stack: Vec<usize> = self.root.into_iter().collect();
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if Metric::Euclidean.distance(&node.coords, coords) <= r {
+                found.push(node.id);
+            }
+            let gap = node.gap_to(coords);
+            if gap <= r {
+                stack.extend(node.left);
+            }
+            if -gap <= r {
+                stack.extend(node.right);
+            }
+        }
+        found.sort();
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Segment;
+    use crate::scheme::SchemeBuilder;
+
+    /// Deterministic pseudo-random points (64-bit LCG).
+    fn random_points(seed: u64, count: usize, dims: usize, range: i64) -> Vec<Vec<i64>> {
+        let mut state = seed;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 33) % range as u64) as i64 - range / 2
+        };
+        (0..count)
+            .map(|_| (0..dims).map(|_| next()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_index_matches_linear_scan() {
+        for dims in [1, 2, 3] {
+            let scheme = SchemeBuilder::new()
+                .add_segments(
+                    random_points(dims as u64, 300, dims, 100)
+                        .into_iter()
+                        .map(Segment::from_values),
+                )
+                .build();
+            let index = SchemeIndex::build(&scheme);
+            assert_eq!(index.len(), scheme.segments().count());
+
+            for query in random_points(99 + dims as u64, 100, dims, 120) {
+                let query = SpaceCoordinates::new(query);
+                for metric in [Metric::Manhattan, Metric::Euclidean, Metric::Chebyshev] {
+                    assert_eq!(
+                        index.nearest(&query, metric),
+                        scheme.nearest_segment(&query, metric).map(|s| *s.id())
+                    );
+                }
+
+                let mut brute: Vec<SegmentId> = scheme
+                    .segments()
+                    .filter(|s| s.distance_to(&query, Metric::Euclidean) <= 15.0)
+                    .map(|s| *s.id())
+                    .collect();
+                brute.sort();
+                assert_eq!(index.within_radius(&query, 15.0), brute);
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_index() {
+        let index = SchemeIndex::build(&SchemeBuilder::new().build());
+        let origin = SpaceCoordinates::new(vec![0, 0]);
+        assert!(index.is_empty());
+        assert_eq!(index.nearest(&origin, Metric::Euclidean), None);
+        assert!(index.within_radius(&origin, 10.0).is_empty());
+    }
+
+    #[test]
+    fn test_queries_at_extreme_coordinates() {
+        let scheme = SchemeBuilder::new()
+            .add_segments([
+                Segment::from_values(vec![i64::MIN, 0]),
+                Segment::from_values(vec![i64::MAX, 0]),
+                Segment::from_values(vec![0, i64::MAX]),
+            ])
+            .build();
+        let index = SchemeIndex::build(&scheme);
+
+        for query in [vec![i64::MAX, 1], vec![i64::MIN, -1], vec![1, i64::MIN]] {
+            let query = SpaceCoordinates::new(query);
+            assert_eq!(
+                index.nearest(&query, Metric::Chebyshev),
+                scheme
+                    .nearest_segment(&query, Metric::Chebyshev)
+                    .map(|s| *s.id())
+            );
+        }
+        assert_eq!(
+            index.within_radius(&SpaceCoordinates::new(vec![i64::MAX, 0]), 1.0),
+            vec![*Segment::from_values(vec![i64::MAX, 0]).id()]
+        );
+    }
+}
@@ -3,9 +3,11 @@
 //! Scheme module -structural blueprint abstraction layer
 
 pub mod abstract_scheme;
+pub mod index;
 pub use abstract_scheme::*;
+pub use index::SchemeIndex;
 
-use crate::core::{Segment, SegmentId, SpaceCoordinates};
+use crate::core::{Segment, SegmentId, SpaceCoordinates, segment_id_from_coords};
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -33,7 +35,11 @@ pub trait SchemeTrait: std::fmt::Debug + Send + Sync {
     fn segments(&self) -> Box<dyn Iterator<Item = &Segment> + '_>;
 
     /// Structural verification
-    fn validate_structure(&self, coords: &SpaceCoordinates) -> Result<(), String>;
+    fn validate_structure(
+        &self,
+        coords: &SpaceCoordinates,
+        segment_id: Option<&SegmentId>,
+    ) -> Result<(), String>;
 
     /// Logical address mapping
     fn map_to_logical_address(&self, coords: &SpaceCoordinates) -> Option<LogicalAddress>;
@@ -104,11 +110,15 @@ impl SchemeTrait for SchemeImpl {
         }
     }
 
-    fn validate_structure(&self, coords: &SpaceCoordinates) -> Result<(), String> {
+    fn validate_structure(
+        &self,
+        coords: &SpaceCoordinates,
+        segment_id: Option<&SegmentId>,
+    ) -> Result<(), String> {
         match self {
-            SchemeImpl::Basic(s) => s.validate_structure(coords),
-            SchemeImpl::Composite(s) => s.validate_structure(coords),
-            SchemeImpl::Transformed(s) => s.validate_structure(coords),
+            SchemeImpl::Basic(s) => s.validate_structure(coords, segment_id),
+            SchemeImpl::Composite(s) => s.validate_structure(coords, segment_id),
+            SchemeImpl::Transformed(s) => s.validate_structure(coords, segment_id),
         }
     }
 
@@ -248,8 +258,13 @@ pub enum ConflictResolution {
 pub struct TransformedScheme {
     id: SchemeId,
     base: Box<SchemeImpl>,
-    #[allow(dead_code)]
     transformation: Transformation,
+    /// (min, max) mirrored around on the reflected axis
+    reflection_extent: Option<(i64, i64)>,
+    /// Transformed segments, keyed by their new identity
+    segments: HashMap<SegmentId, Segment>,
+    /// Base relations re-keyed onto the transformed segments
+    relations: RelationGraph,
 }
 
 impl TransformedScheme {
@@ -294,6 +309,10 @@ impl TransformedScheme {
             TransformType::TopologicalTransform => {
                 hasher.update(b"TopologicalTransform");
             }
+            TransformType::Reflection(axis) => {
+                hasher.update(b"Reflection");
+                hasher.update(&(*axis as u64).to_be_bytes());
+            }
         }
         // Include parameters (sorted for deterministic hash)
         let mut param_keys: Vec<_> = transformation.parameters.keys().collect();
@@ -305,12 +324,85 @@ impl TransformedScheme {
             }
         }
         let id = SchemeId(hasher.finalize().into());
-        Self {
+
+        // Prefer the declared axis range; fall back to the extent of the base segments.
+        let reflection_extent = match transformation.transform_type {
+            TransformType::Reflection(axis) => {
+                base.axes().get(axis).and_then(Axis::range).or_else(|| {
+                    base.segments()
+                        .filter_map(|s| s.coordinates().get_axis(axis))
+                        .fold(None, |acc, v| match acc {
+                            None => Some((v, v)),
+                            Some((lo, hi)) => Some((lo.min(v), hi.max(v))),
+                        })
+                })
+            }
+            _ => None,
+        };
+
+        let mut scheme = Self {
             id,
             base,
             transformation,
+            reflection_extent,
+            segments: HashMap::new(),
+            relations: RelationGraph::new(),
+        };
+
+        // Materialise transformed segments and rebuild relations on the new ids.
+        let mut id_map = HashMap::new();
+        for segment in scheme.base.segments() {
+            let transformed = Segment::new(scheme.apply_transform(segment.coordinates()));
+            id_map.insert(*segment.id(), *transformed.id());
+            scheme.segments.insert(*transformed.id(), transformed);
+        }
+        let mut relations = RelationGraph::new();
+        for (from, to, relation) in relation_edges(&scheme.base) {
+            let from = id_map.get(from).copied().unwrap_or(*from);
+            let to = id_map.get(to).copied().unwrap_or(*to);
+            relations.add_relation(from, to, relation.clone());
+        }
+        scheme.relations = relations;
+        scheme
+    }
+
+    /// Apply the transformation to base‑space coordinates.
+    ///
+    /// `Reflection(axis)` mirrors the axis around the midpoint of its declared
+    /// [`Axis::range`], or of the base segments' extent on that axis when no range
+    /// is recorded (`v ↦ min + max − v`, saturating at the `i64` bounds); being an
+    /// involution, it also maps transformed coordinates back to base space.
+    /// Other transform types are not materialised yet and leave coordinates unchanged.
+    pub fn apply_transform(&self, coords: &SpaceCoordinates) -> SpaceCoordinates {
+        match (&self.transformation.transform_type, self.reflection_extent) {
+            (TransformType::Reflection(axis), Some((lo, hi))) => {
+                let mut raw = coords.raw.clone();
+                if let Some(v) = raw.get_mut(*axis) {
+                    let mirrored = lo as i128 + hi as i128 - *v as i128;
+                    *v = mirrored.clamp(i64::MIN as i128, i64::MAX as i128) as i64;
+                }
+                SpaceCoordinates::new(raw)
+            }
+            _ => coords.clone(),
         }
     }
+
+    /// Structural relations of the base scheme, re‑keyed onto transformed segments.
+    pub fn relations(&self) -> &RelationGraph {
+        &self.relations
+    }
+}
+
+/// Structural relations of a scheme; a composite contributes the union of its
+/// components' relations.
+fn relation_edges(
+    scheme: &SchemeImpl,
+) -> Box<dyn Iterator<Item = (&SegmentId, &SegmentId, &StructuralRelation)> + '_> {
+    match scheme {
+        SchemeImpl::Basic(s) => Box::new(s.relations().edges()),
+        SchemeImpl::Composite(c) => Box::new(c.components.iter().flat_map(relation_edges)),
+        SchemeImpl::Transformed(t) => Box::new(t.relations.edges()),
+    }
 }
 
 /// Scheme conversion
@@ -330,6 +422,7 @@ pub enum TransformType {
     DimensionalReduction,    // dimensionality reduction
     DimensionalExpansion,    // dimension expansion
     TopologicalTransform,    // Topology Transformation
+    Reflection(usize),       // mirror across an axis (axis index)
 }
 
 /// Simple matrix type (actual implementation omitted)
@@ -375,7 +468,11 @@ impl SchemeTrait for CompositeScheme {
         Box::new(iter)
     }
 
-    fn validate_structure(&self, _coords: &SpaceCoordinates) -> Result<(), String> {
+    fn validate_structure(
+        &self,
+        _coords: &SpaceCoordinates,
+        _segment_id: Option<&SegmentId>,
+    ) -> Result<(), String> {
         // For composite schemes, validation may be complex; just return OK.
         Ok(())
     }
@@ -404,25 +501,33 @@ impl SchemeTrait for TransformedScheme {
     }
 
     fn contains_segment(&self, segment_id: &SegmentId) -> bool {
-        self.base.contains_segment(segment_id)
+        self.segments.contains_key(segment_id)
     }
 
     fn get_segment(&self, segment_id: &SegmentId) -> Option<&Segment> {
-        self.base.get_segment(segment_id)
+        self.segments.get(segment_id)
     }
 
     fn segments(&self) -> Box<dyn Iterator<Item = &Segment> + '_> {
-        self.base.segments()
+        Box::new(self.segments.values())
     }
 
-    fn validate_structure(&self, coords: &SpaceCoordinates) -> Result<(), String> {
-        // Apply transformation before validation? For now delegate to base.
-        self.base.validate_structure(coords)
+    fn validate_structure(
+        &self,
+        coords: &SpaceCoordinates,
+        segment_id: Option<&SegmentId>,
+    ) -> Result<(), String> {
+        // Map back into base space before delegating; segment identities are
+        // coordinate-derived, so the base id follows from the base coordinates.
+        let base_coords = self.apply_transform(coords);
+        let base_id = segment_id.map(|_| segment_id_from_coords(&base_coords));
+        self.base.validate_structure(&base_coords, base_id.as_ref())
     }
 
     fn map_to_logical_address(&self, coords: &SpaceCoordinates) -> Option<LogicalAddress> {
-        // Apply transformation to coordinates before mapping? For now delegate.
-        self.base.map_to_logical_address(coords)
+        // Map back into base space before delegating.
+        self.base
+            .map_to_logical_address(&self.apply_transform(coords))
     }
 
     fn describe(&self) -> String {
@@ -434,3 +539,154 @@ impl SchemeTrait for TransformedScheme {
 pub use abstract_scheme::graph::GraphTemplate;
 pub use abstract_scheme::grid2d::Grid2DTemplate;
 pub use abstract_scheme::integer_line::IntegerLineTemplate;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transformed(base: Scheme, transform_type: TransformType) -> TransformedScheme {
+        TransformedScheme::new(
+            Box::new(SchemeImpl::Basic(Box::new(base))),
+            Transformation {
+                transform_type,
+                parameters: HashMap::new(),
+            },
+        )
+    }
+
+    #[test]
+    fn test_reflection_mirrors_line() {
+        let (a, b, c) = (
+            Segment::from_value(0),
+            Segment::from_value(1),
+            Segment::from_value(4),
+        );
+        let base = SchemeBuilder::new()
+            .add_axis(Axis {
+                name: "x".to_string(),
+                axis_type: AxisType::Discrete,
+                metadata: HashMap::new(),
+            })
+            .add_segments([a.clone(), b.clone(), c])
+            .add_relation(
+                *a.id(),
+                *b.id(),
+                StructuralRelation::Adjacency {
+                    relation_type: AdjacencyType::Graph,
+                    weight: None,
+                    metadata: HashMap::new(),
+                },
+            )
+            .build();
+
+        let reflected = transformed(base, TransformType::Reflection(0));
+        let mut values: Vec<i64> = reflected
+            .segments()
+            .map(|s| s.coordinates().raw[0])
+            .collect();
+        values.sort();
+        assert_eq!(values, vec![0, 3, 4]);
+        assert!(!reflected.contains_segment(b.id()));
+
+        // 0 → 1 becomes 4 → 3
+        let from = Segment::from_value(4);
+        let to = Segment::from_value(3);
+        assert_eq!(
+            reflected
+                .relations()
+                .get_relations_between(from.id(), to.id())
+                .len(),
+            1
+        );
+    }
+
+    fn line_axis(range: Option<(i64, i64)>) -> Axis {
+        let mut metadata = HashMap::new();
+        if let Some((start, end)) = range {
+            metadata.insert("range_start".to_string(), start.to_string());
+            metadata.insert("range_end".to_string(), end.to_string());
+        }
+        Axis {
+            name: "x".to_string(),
+            axis_type: AxisType::Discrete,
+            metadata,
+        }
+    }
+
+    #[test]
+    fn test_reflection_mirrors_around_declared_range() {
+        let base = SchemeBuilder::new()
+            .add_axis(line_axis(Some((0, 10))))
+            .add_segments([Segment::from_value(1), Segment::from_value(4)])
+            .build();
+
+        let reflected = transformed(base, TransformType::Reflection(0));
+        let mut values: Vec<i64> = reflected
+            .segments()
+            .map(|s| s.coordinates().raw[0])
+            .collect();
+        values.sort();
+        assert_eq!(values, vec![6, 9]);
+    }
+
+    #[test]
+    fn test_reflection_saturates_at_extreme_coordinates() {
+        let base = SchemeBuilder::new()
+            .add_axis(line_axis(Some((i64::MAX - 1, i64::MAX))))
+            .add_segments([Segment::from_value(i64::MAX)])
+            .build();
+
+        let reflected = transformed(base, TransformType::Reflection(0));
+        let values: Vec<i64> = reflected
+            .segments()
+            .map(|s| s.coordinates().raw[0])
+            .collect();
+        assert_eq!(values, vec![i64::MAX - 1]);
+        assert_eq!(
+            reflected.apply_transform(&SpaceCoordinates::new(vec![i64::MIN])),
+            SpaceCoordinates::new(vec![i64::MAX])
+        );
+    }
+
+    #[test]
+    fn test_reflection_keeps_relations_of_composite_base() {
+        let (a, b) = (Segment::from_value(0), Segment::from_value(1));
+        let line = SchemeBuilder::new()
+            .add_axis(line_axis(None))
+            .add_segments([a.clone(), b.clone()])
+            .add_relation(
+                *a.id(),
+                *b.id(),
+                StructuralRelation::Adjacency {
+                    relation_type: AdjacencyType::Graph,
+                    weight: None,
+                    metadata: HashMap::new(),
+                },
+            )
+            .build();
+        let composite = CompositeScheme::new(
+            vec![SchemeImpl::Basic(Box::new(line))],
+            CompositionRules {
+                combination_method: CombinationMethod::Union,
+                alignment: None,
+                conflict_resolution: ConflictResolution::FirstWins,
+            },
+        );
+
+        let reflected = TransformedScheme::new(
+            Box::new(SchemeImpl::Composite(composite)),
+            Transformation {
+                transform_type: TransformType::Reflection(0),
+                parameters: HashMap::new(),
+            },
+        );
+        // 0 → 1 becomes 1 → 0
+        assert_eq!(
+            reflected
+                .relations()
+                .get_relations_between(b.id(), a.id())
+                .len(),
+            1
+        );
+    }
+}
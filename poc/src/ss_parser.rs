@@ -7,10 +7,20 @@
 //! This module provides a `parse` function that reads a binary blob and
 //! reconstructs a `Scheme` instance.
 
-use crate::core::Segment;
+use crate::core::{Field, Segment, SpaceCoordinates};
 use crate::scheme::abstract_scheme::{Axis, AxisType, Scheme, SchemeBuilder};
 use std::collections::HashMap;
-use std::io::{Read, Seek};
+use std::io::{Read, Seek, Write};
+
+const MAGIC: &[u8; 4] = b".ss\0";
+const VERSION: u8 = 1;
+
+/// Tag of the optional transition‑matrix section.
+///
+/// Layout (little endian): `tag: u8 | count: u32 | count × edge`, where
+/// `edge = from: coords | to: coords | weight: f64` and
+/// `coords = dims: u32 | dims × i64`.
+const SECTION_TRANSITIONS: u8 = b'T';
 
 /// Error type for parsing failures.
 #[derive(Debug, thiserror::Error)]
@@ -30,10 +40,47 @@ pub enum ParseError {
 /// The stream must implement `Read + Seek`. The parser validates the header,
 /// reads all sections, and builds a `Scheme` using the `SchemeBuilder`.
 pub fn parse<R: Read + Seek>(mut reader: R) -> Result<Scheme, ParseError> {
-    // 1. Header
+    read_header(&mut reader)?;
+    Ok(read_scheme(&mut reader))
+}
+
+/// Parses a binary `.ss` stream into a `Scheme` plus a companion `Field`
+/// carrying the optional transition‑matrix section.
+/// A stream without that section yields an empty `Field`.
+pub fn parse_with_field<R: Read + Seek>(mut reader: R) -> Result<(Scheme, Field), ParseError> {
+    read_header(&mut reader)?;
+    let scheme = read_scheme(&mut reader);
+
+    let mut field = Field::new();
+    let mut tag = [0u8; 1];
+    match reader.read_exact(&mut tag) {
+        Ok(()) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+            return Ok((scheme, field));
+        }
+        Err(err) => return Err(err.into()),
+    }
+    if tag[0] != SECTION_TRANSITIONS {
+        return Err(ParseError::Malformed(format!(
+            "unknown section tag 0x{:02x}",
+            tag[0]
+        )));
+    }
+    let count = read_u32(&mut reader)?;
+    for _ in 0..count {
+        let from = read_coords(&mut reader)?;
+        let to = read_coords(&mut reader)?;
+        let mut weight = [0u8; 8];
+        reader.read_exact(&mut weight)?;
+        field.add_transition(from, to, f64::from_le_bytes(weight));
+    }
+    Ok((scheme, field))
+}
+
+fn read_header<R: Read>(reader: &mut R) -> Result<(), ParseError> {
     let mut magic = [0u8; 4];
     reader.read_exact(&mut magic)?;
-    if &magic != b".ss\0" {
+    if &magic != MAGIC {
         return Err(ParseError::InvalidMagic);
     }
     let version = {
@@ -41,37 +88,95 @@ pub fn parse<R: Read + Seek>(mut reader: R) -> Result<Scheme, ParseError> {
         reader.read_exact(&mut buf)?;
         buf[0]
     };
-    if version != 1 {
+    if version != VERSION {
         return Err(ParseError::UnsupportedVersion(version));
     }
+    Ok(())
+}
 
+fn read_scheme<R: Read>(_reader: &mut R) -> Scheme {
     // For now, we return a dummy Scheme.
     // TODO: implement full parsing of axis, segment, relation, memory layout,
     // observation rules, and constraints.
-    let dummy_scheme = SchemeBuilder::new()
+    SchemeBuilder::new()
         .add_axis(Axis {
             name: "x".to_string(),
             axis_type: AxisType::Discrete,
             metadata: HashMap::new(),
         })
         .add_segment(Segment::from_values(vec![0]))
-        .build();
+        .build()
+}
 
-    Ok(dummy_scheme)
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, ParseError> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_coords<R: Read>(reader: &mut R) -> Result<SpaceCoordinates, ParseError> {
+    let dims = read_u32(reader)?;
+    let mut raw = Vec::with_capacity(dims.min(64) as usize);
+    for _ in 0..dims {
+        let mut buf = [0u8; 8];
+        reader.read_exact(&mut buf)?;
+        raw.push(i64::from_le_bytes(buf));
+    }
+    Ok(SpaceCoordinates::new(raw))
 }
 
 /// Serializes a `Scheme` into the binary `.ss` format.
 ///
-/// This is a placeholder; a real implementation would write the header,
-/// axis list, segment table, relation graph, memory‑layout description,
-/// observation rules, and structural constraints.
-pub fn serialize<W: std::io::Write>(_scheme: &Scheme, _writer: W) -> Result<(), ParseError> {
-    todo!("Binary serialization not yet implemented")
+/// **The Scheme itself is not persisted yet**: only the header is written, so
+/// `parse` of the output yields the placeholder Scheme, not `scheme`. A full
+/// implementation would add the axis list, segment table, relation graph,
+/// memory‑layout description, observation rules, and structural constraints.
+pub fn serialize<W: Write>(_scheme: &Scheme, mut writer: W) -> Result<(), ParseError> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[VERSION])?;
+    Ok(())
+}
+
+/// Serializes a `Scheme` followed by the `Field`'s transition‑matrix section.
+/// Only transitions with known endpoint coordinates are written.
+///
+/// As with `serialize`, the Scheme body is not persisted: only the Field
+/// round‑trips through `parse_with_field`, which pairs it with the placeholder
+/// Scheme.
+pub fn serialize_with_field<W: Write>(
+    scheme: &Scheme,
+    field: &Field,
+    mut writer: W,
+) -> Result<(), ParseError> {
+    serialize(scheme, &mut writer)?;
+
+    let edges = field.transitions().edges();
+    let count = u32::try_from(edges.len())
+        .map_err(|_| ParseError::Malformed("too many transitions".to_string()))?;
+    writer.write_all(&[SECTION_TRANSITIONS])?;
+    writer.write_all(&count.to_le_bytes())?;
+    for (from, to, weight) in edges {
+        write_coords(&mut writer, &from)?;
+        write_coords(&mut writer, &to)?;
+        writer.write_all(&weight.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+fn write_coords<W: Write>(writer: &mut W, coords: &SpaceCoordinates) -> Result<(), ParseError> {
+    let dims = u32::try_from(coords.dimensionality())
+        .map_err(|_| ParseError::Malformed("too many dimensions".to_string()))?;
+    writer.write_all(&dims.to_le_bytes())?;
+    for v in &coords.raw {
+        writer.write_all(&v.to_le_bytes())?;
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::scheme::IntegerLineTemplate;
     use std::io::Cursor;
 
     #[test]
@@ -93,4 +198,29 @@ mod tests {
         let result = parse(cursor);
         assert!(matches!(result, Err(ParseError::InvalidMagic)));
     }
+
+    #[test]
+    fn test_transition_section_round_trip() {
+        let a = SpaceCoordinates::new(vec![0, 0]);
+        let b = SpaceCoordinates::new(vec![1, 0]);
+        let c = SpaceCoordinates::new(vec![1, 1]);
+        let mut field = Field::new();
+        field.add_transition(a.clone(), b.clone(), 0.25);
+        field.add_transition(b.clone(), c.clone(), 0.75);
+
+        let scheme = IntegerLineTemplate::new(0, 4, 1).build();
+        let mut data = vec![];
+        serialize_with_field(&scheme, &field, &mut data).unwrap();
+
+        let (_, parsed) = parse_with_field(Cursor::new(data)).unwrap();
+        assert_eq!(parsed.transitions().edges(), field.transitions().edges());
+        assert_eq!(parsed.transitions().get_weight(&a, &b), Some(0.25));
+        assert_eq!(parsed.transitions().get_weight(&b, &c), Some(0.75));
+    }
+
+    #[test]
+    fn test_parse_with_field_without_section() {
+        let (_, field) = parse_with_field(Cursor::new(b".ss\0\x01".to_vec())).unwrap();
+        assert!(field.transitions().edges().is_empty());
+    }
 }